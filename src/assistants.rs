@@ -0,0 +1,246 @@
+use crate::construct::{DeletionStatus, Page};
+use crate::error::OpenAIResult;
+use crate::param::{FunctionDefinition, ListParams};
+use crate::Client;
+use chrono::serde::ts_seconds_option;
+use chrono::{DateTime, Utc};
+use futures::Stream;
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Assistant
+/// {
+/// "id": "asst_abc123",
+/// "object": "assistant",
+/// "created_at": 1698984975,
+/// "name": "Math Tutor",
+/// "model": "gpt-4o",
+/// "instructions": "You are a personal math tutor.",
+/// "tools": [...]
+/// }
+#[derive(Deserialize, Debug)]
+pub struct Assistant {
+    pub id: String,
+    pub object: String,
+    #[serde(with = "ts_seconds_option")]
+    pub created_at: Option<DateTime<Utc>>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub model: String,
+    pub instructions: Option<String>,
+    #[serde(default)]
+    pub tools: Vec<AssistantTool>,
+    pub tool_resources: Option<ToolResources>,
+    pub metadata: Option<HashMap<String, String>>,
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+}
+
+/// Tools that can be enabled on an assistant or on a single run
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AssistantTool {
+    CodeInterpreter,
+    FileSearch,
+    Function { function: FunctionDefinition },
+}
+
+/// Files and vector stores made available to the `code_interpreter` and `file_search` tools
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct ToolResources {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code_interpreter: Option<CodeInterpreterResources>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_search: Option<FileSearchResources>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct CodeInterpreterResources {
+    #[serde(default)]
+    pub file_ids: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct FileSearchResources {
+    #[serde(default)]
+    pub vector_store_ids: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AssistantParams {
+    pub model: String,
+    #[serde(flatten)]
+    pub opts: OptAssistantParams,
+}
+
+/// Every field is also accepted when modifying an assistant
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct OptAssistantParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<AssistantTool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_resources: Option<ToolResources>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ModifyAssistantParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(flatten)]
+    pub opts: OptAssistantParams,
+}
+
+impl Client {
+    /// Creates an assistant with a model and instructions
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _the requested model doesn't exist_
+    /// * _endpoint is unavailable_
+    /// * _deserialization of JSON Assistant data fails_
+    #[tokio::main]
+    pub async fn create_assistant(&self, params: AssistantParams) -> OpenAIResult<Assistant> {
+        let url = format!("{}/assistants", self.api_base);
+
+        self.send(self.beta_request(Method::POST, url).json(&params))
+            .await
+    }
+
+    /// Retrieves an assistant by its id
+    #[tokio::main]
+    pub async fn retrieve_assistant(&self, assistant_id: &str) -> OpenAIResult<Assistant> {
+        let url = format!("{}/assistants/{}", self.api_base, assistant_id);
+
+        self.send(self.beta_request(Method::GET, url)).await
+    }
+
+    /// Modifies an assistant. Only the fields that are set are changed
+    #[tokio::main]
+    pub async fn modify_assistant(
+        &self,
+        assistant_id: &str,
+        params: ModifyAssistantParams,
+    ) -> OpenAIResult<Assistant> {
+        let url = format!("{}/assistants/{}", self.api_base, assistant_id);
+
+        self.send(self.beta_request(Method::POST, url).json(&params))
+            .await
+    }
+
+    #[tokio::main]
+    pub async fn delete_assistant(&self, assistant_id: &str) -> OpenAIResult<DeletionStatus> {
+        let url = format!("{}/assistants/{}", self.api_base, assistant_id);
+
+        self.send(self.beta_request(Method::DELETE, url)).await
+    }
+
+    /// Returns one page of assistants. Use `ListParams::after` to fetch the following pages
+    #[tokio::main]
+    pub async fn list_assistants(&self, params: ListParams) -> OpenAIResult<Page<Assistant>> {
+        let url = format!("{}/assistants", self.api_base);
+
+        self.send(self.beta_request(Method::GET, url).query(&params))
+            .await
+    }
+
+    /// Lazily walks every assistant, fetching further pages as needed
+    ///
+    /// The stream has to be polled inside a Tokio runtime
+    pub fn list_all_assistants(
+        &self,
+        params: ListParams,
+    ) -> impl Stream<Item = OpenAIResult<Assistant>> + '_ {
+        let url = format!("{}/assistants", self.api_base);
+
+        self.paginate(
+            move |query| self.beta_request(Method::GET, url.clone()).query(query),
+            params,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assistant_deserialization() {
+        let assistant_data = r#"
+        {
+            "id": "asst_abc123",
+            "object": "assistant",
+            "created_at": 1698984975,
+            "name": "Math Tutor",
+            "description": null,
+            "model": "gpt-4o",
+            "instructions": "You are a personal math tutor.",
+            "tools": [
+                { "type": "code_interpreter" },
+                {
+                    "type": "function",
+                    "function": {
+                        "name": "get_weather",
+                        "description": "Get the weather for a city",
+                        "parameters": { "type": "object", "properties": { "city": { "type": "string" } } }
+                    }
+                }
+            ],
+            "tool_resources": { "code_interpreter": { "file_ids": ["file-abc"] } },
+            "metadata": {},
+            "top_p": 1.0,
+            "temperature": 1.0,
+            "response_format": "auto"
+        }"#;
+
+        let assistant: Assistant = serde_json::from_str(assistant_data).unwrap();
+
+        assert_eq!(assistant.name, Some(String::from("Math Tutor")));
+        assert_eq!(assistant.tools[0], AssistantTool::CodeInterpreter);
+        match &assistant.tools[1] {
+            AssistantTool::Function { function } => assert_eq!(function.name, "get_weather"),
+            other => panic!("Expected a function tool, got {:?}", other),
+        }
+        assert_eq!(
+            assistant
+                .tool_resources
+                .unwrap()
+                .code_interpreter
+                .unwrap()
+                .file_ids,
+            vec![String::from("file-abc")]
+        );
+    }
+
+    #[test]
+    fn test_assistant_params_serialization() {
+        let params = AssistantParams {
+            model: String::from("gpt-4o"),
+            opts: OptAssistantParams {
+                name: Some(String::from("Math Tutor")),
+                tools: Some(vec![AssistantTool::FileSearch]),
+                ..Default::default()
+            },
+        };
+
+        let params_serialized = serde_json::to_string(&params).unwrap();
+
+        let params_json =
+            r#"{"model":"gpt-4o","name":"Math Tutor","tools":[{"type":"file_search"}]}"#;
+
+        assert_eq!(params_serialized, params_json);
+    }
+}
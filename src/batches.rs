@@ -0,0 +1,124 @@
+use crate::construct::Page;
+use crate::error::OpenAIResult;
+use crate::param::ListParams;
+use crate::Client;
+use chrono::serde::ts_seconds_option;
+use chrono::{DateTime, Utc};
+use futures::Stream;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Batch
+/// {
+/// "id": "batch_abc123",
+/// "object": "batch",
+/// "endpoint": "/v1/chat/completions",
+/// "input_file_id": "file-abc123",
+/// "completion_window": "24h",
+/// "status": "completed",
+/// "request_counts": {...},
+/// ...
+/// }
+#[derive(Deserialize, Debug)]
+pub struct Batch {
+    pub id: String,
+    pub object: String,
+    pub endpoint: String,
+    pub input_file_id: String,
+    pub completion_window: String,
+    pub status: BatchStatus,
+    pub output_file_id: Option<String>,
+    pub error_file_id: Option<String>,
+    #[serde(with = "ts_seconds_option")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, with = "ts_seconds_option")]
+    pub completed_at: Option<DateTime<Utc>>,
+    #[serde(default, with = "ts_seconds_option")]
+    pub expires_at: Option<DateTime<Utc>>,
+    pub request_counts: Option<RequestCounts>,
+    pub metadata: Option<HashMap<String, String>>,
+    /// Fields this crate doesn't know about yet
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchStatus {
+    Validating,
+    Failed,
+    InProgress,
+    Finalizing,
+    Completed,
+    Expired,
+    Cancelling,
+    Cancelled,
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+pub struct RequestCounts {
+    pub total: u32,
+    pub completed: u32,
+    pub failed: u32,
+}
+
+impl Client {
+    fn batches_url(&self) -> String {
+        format!("{}/batches", self.api_base)
+    }
+}
+
+impl Client {
+    /// Lists the batches. The endpoint only takes the `after` cursor and `limit`
+    #[tokio::main]
+    pub async fn list_batches(&self, params: ListParams) -> OpenAIResult<Page<Batch>> {
+        self.send(self.http_client.get(self.batches_url()).query(&params))
+            .await
+    }
+
+    /// Lazily walks every batch, fetching further pages as needed
+    ///
+    /// The stream has to be polled inside a Tokio runtime
+    pub fn list_all_batches(
+        &self,
+        params: ListParams,
+    ) -> impl Stream<Item = OpenAIResult<Batch>> + '_ {
+        self.paginate(
+            move |query| self.http_client.get(self.batches_url()).query(query),
+            params,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_deserialization() {
+        let batch_data = r#"
+        {
+            "id": "batch_abc123",
+            "object": "batch",
+            "endpoint": "/v1/chat/completions",
+            "errors": null,
+            "input_file_id": "file-abc123",
+            "completion_window": "24h",
+            "status": "completed",
+            "output_file_id": "file-cvaTdG",
+            "error_file_id": "file-HOWS94",
+            "created_at": 1711471533,
+            "completed_at": 1711493163,
+            "expires_at": 1711557933,
+            "request_counts": { "total": 100, "completed": 95, "failed": 5 },
+            "metadata": { "customer_id": "user_123456789" }
+        }"#;
+
+        let batch: Batch = serde_json::from_str(batch_data).unwrap();
+
+        assert_eq!(batch.status, BatchStatus::Completed);
+        assert_eq!(batch.request_counts.unwrap().failed, 5);
+        assert!(batch.completed_at.is_some());
+    }
+}
@@ -1,33 +1,44 @@
+use crate::param::{ListParams, Message};
 use chrono::serde::ts_seconds_option;
 use chrono::{DateTime, Utc};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign};
 
-#[derive(Deserialize, Debug)]
-pub struct ModelList {
-    pub data: Vec<Model>,
-    pub object: String,
-}
+/// The models endpoint isn't paginated, so the whole list always comes back as a single page
+pub type ModelList = Page<Model>;
 
 /// Model
 /// {
 /// "id": "model-id-0",
 /// "object": "model",
-/// "owned_by": "organization-owner",
-/// "permission": [...]
+/// "created": 1686935002,
+/// "owned_by": "organization-owner"
 /// }
+///
+/// `permission`, `root` and `parent` are only returned by older versions of the API
 #[derive(Deserialize, Debug)]
 pub struct Model {
     pub id: String,
     pub object: String,
-    #[serde(with = "ts_seconds_option")]
+    #[serde(with = "ts_seconds_option", default)]
     pub created: Option<DateTime<Utc>>,
     pub owned_by: String,
+    #[serde(default)]
     pub permission: Vec<Permission>,
-    root: String,
-    parent: Option<String>,
+    #[serde(default)]
+    pub root: Option<String>,
+    #[serde(default)]
+    pub parent: Option<String>,
+    /// Fields this crate doesn't know about yet
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
 pub struct Permission {
     pub id: String,
     pub object: String,
@@ -39,44 +50,498 @@ pub struct Permission {
     pub allow_search_indices: bool,
     pub allow_view: bool,
     pub allow_fine_tuning: bool,
-    pub organization: String,
+    pub organization: Option<String>,
     pub group: Option<String>,
     pub is_blocking: bool,
+    /// Fields this crate doesn't know about yet
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-#[derive(Deserialize, Debug)]
+/// Token counts of a request, which can be added up across requests
+///
+/// The details are only returned by newer models. Unknown counters in `extra` aren't added up,
+/// and are left out of sums
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
 pub struct Usage {
-    pub prompt_tokens: u16,
-    pub completion_tokens: u16,
-    pub total_tokens: u16,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+    #[serde(default)]
+    pub prompt_tokens_details: Option<PromptTokensDetails>,
+    #[serde(default)]
+    pub completion_tokens_details: Option<CompletionTokensDetails>,
+    /// Fields this crate doesn't know about yet
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default)]
+pub struct PromptTokensDetails {
+    /// Prompt tokens read from the prompt cache, billed at a discount
+    pub cached_tokens: u64,
+    pub audio_tokens: u64,
+    /// Fields this crate doesn't know about yet
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default)]
+pub struct CompletionTokensDetails {
+    /// Tokens spent on hidden reasoning, counted in `completion_tokens`
+    pub reasoning_tokens: u64,
+    pub audio_tokens: u64,
+    /// Tokens of a predicted output that appeared in the completion
+    pub accepted_prediction_tokens: u64,
+    /// Tokens of a predicted output that didn't, still billed as completion tokens
+    pub rejected_prediction_tokens: u64,
+    /// Fields this crate doesn't know about yet
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Add for Usage {
+    type Output = Usage;
+
+    fn add(self, rhs: Usage) -> Usage {
+        Usage {
+            prompt_tokens: self.prompt_tokens + rhs.prompt_tokens,
+            completion_tokens: self.completion_tokens + rhs.completion_tokens,
+            total_tokens: self.total_tokens + rhs.total_tokens,
+            prompt_tokens_details: add_details(
+                self.prompt_tokens_details,
+                rhs.prompt_tokens_details,
+            ),
+            completion_tokens_details: add_details(
+                self.completion_tokens_details,
+                rhs.completion_tokens_details,
+            ),
+            extra: Map::new(),
+        }
+    }
+}
+
+impl Add for PromptTokensDetails {
+    type Output = PromptTokensDetails;
+
+    fn add(self, rhs: PromptTokensDetails) -> PromptTokensDetails {
+        PromptTokensDetails {
+            cached_tokens: self.cached_tokens + rhs.cached_tokens,
+            audio_tokens: self.audio_tokens + rhs.audio_tokens,
+            extra: Map::new(),
+        }
+    }
+}
+
+impl Add for CompletionTokensDetails {
+    type Output = CompletionTokensDetails;
+
+    fn add(self, rhs: CompletionTokensDetails) -> CompletionTokensDetails {
+        CompletionTokensDetails {
+            reasoning_tokens: self.reasoning_tokens + rhs.reasoning_tokens,
+            audio_tokens: self.audio_tokens + rhs.audio_tokens,
+            accepted_prediction_tokens: self.accepted_prediction_tokens
+                + rhs.accepted_prediction_tokens,
+            rejected_prediction_tokens: self.rejected_prediction_tokens
+                + rhs.rejected_prediction_tokens,
+            extra: Map::new(),
+        }
+    }
+}
+
+/// Details missing on one side count as zero
+fn add_details<T: Add<Output = T>>(lhs: Option<T>, rhs: Option<T>) -> Option<T> {
+    match (lhs, rhs) {
+        (Some(lhs), Some(rhs)) => Some(lhs + rhs),
+        (lhs, rhs) => lhs.or(rhs),
+    }
+}
+
+impl AddAssign for Usage {
+    fn add_assign(&mut self, rhs: Usage) {
+        *self = std::mem::take(self) + rhs;
+    }
+}
+
+impl Sum for Usage {
+    fn sum<I: Iterator<Item = Usage>>(iter: I) -> Usage {
+        iter.fold(Usage::default(), Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Usage> for Usage {
+    fn sum<I: Iterator<Item = &'a Usage>>(iter: I) -> Usage {
+        iter.cloned().sum()
+    }
 }
 
 #[derive(Deserialize, Debug)]
 pub struct Choice {
     pub text: String,
     pub index: usize,
-    pub logprobs: Option<usize>,
-    pub finish_reason: String,
+    #[serde(default)]
+    pub logprobs: Option<Logprobs>,
+    #[serde(default)]
+    pub finish_reason: Option<FinishReason>,
+    /// Fields this crate doesn't know about yet
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Why the model stopped generating, `None` on streamed chunks before the last one
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
+pub enum FinishReason {
+    /// A natural stopping point or a stop sequence was reached
+    Stop,
+    /// `max_tokens` or the context length was reached, so the output is cut off
+    Length,
+    ContentFilter,
+    ToolCalls,
+    /// The deprecated `function_call` equivalent of `ToolCalls`
+    FunctionCall,
+    /// Reasons this crate doesn't know about yet
+    Other(String),
+}
+
+impl From<String> for FinishReason {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "stop" => FinishReason::Stop,
+            "length" => FinishReason::Length,
+            "content_filter" => FinishReason::ContentFilter,
+            "tool_calls" => FinishReason::ToolCalls,
+            "function_call" => FinishReason::FunctionCall,
+            _ => FinishReason::Other(value),
+        }
+    }
+}
+
+impl From<FinishReason> for String {
+    fn from(value: FinishReason) -> Self {
+        match value {
+            FinishReason::Stop => String::from("stop"),
+            FinishReason::Length => String::from("length"),
+            FinishReason::ContentFilter => String::from("content_filter"),
+            FinishReason::ToolCalls => String::from("tool_calls"),
+            FinishReason::FunctionCall => String::from("function_call"),
+            FinishReason::Other(reason) => reason,
+        }
+    }
+}
+
+/// Logprobs
+/// {
+/// "tokens": [" test", "."],
+/// "token_logprobs": [-0.03, -1.2],
+/// "top_logprobs": [{" test": -0.03, " trial": -3.6}, {".": -1.2, "!": -0.5}],
+/// "text_offset": [10, 15]
+/// }
+///
+/// The first token has no logprob when the prompt is echoed
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Logprobs {
+    pub tokens: Vec<String>,
+    pub token_logprobs: Vec<Option<f64>>,
+    #[serde(default)]
+    pub top_logprobs: Vec<Option<HashMap<String, f64>>>,
+    pub text_offset: Vec<usize>,
+    /// Fields this crate doesn't know about yet
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Logprobs {
+    /// The probability of each sampled token
+    pub fn probabilities(&self) -> Vec<Option<f64>> {
+        self.token_logprobs
+            .iter()
+            .map(|logprob| logprob.map(f64::exp))
+            .collect()
+    }
+
+    /// The perplexity of the sampled tokens, lower meaning the model was more confident
+    pub fn perplexity(&self) -> Option<f64> {
+        perplexity(self.token_logprobs.iter().flatten().copied())
+    }
+
+    /// The most likely token other than the sampled one at each position, with its logprob
+    pub fn best_alternatives(&self) -> Vec<Option<(&str, f64)>> {
+        self.tokens
+            .iter()
+            .zip(&self.top_logprobs)
+            .map(|(token, top)| {
+                top.iter()
+                    .flatten()
+                    .filter(|(candidate, _)| *candidate != token)
+                    .max_by(|a, b| a.1.total_cmp(b.1))
+                    .map(|(candidate, logprob)| (candidate.as_str(), *logprob))
+            })
+            .collect()
+    }
+}
+
+/// Chat completion logprobs, for the tokens of either the content or the refusal
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ChatLogprobs {
+    pub content: Option<Vec<TokenLogprob>>,
+    #[serde(default)]
+    pub refusal: Option<Vec<TokenLogprob>>,
+    /// Fields this crate doesn't know about yet
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl ChatLogprobs {
+    /// The perplexity of the content tokens, lower meaning the model was more confident
+    pub fn perplexity(&self) -> Option<f64> {
+        perplexity(self.content.iter().flatten().map(|token| token.logprob))
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct TokenLogprob {
+    pub token: String,
+    pub logprob: f64,
+    /// The UTF-8 bytes of the token, since a token can hold part of a character
+    pub bytes: Option<Vec<u8>>,
+    /// Only filled when `top_logprobs` is requested
+    #[serde(default)]
+    pub top_logprobs: Vec<TopLogprob>,
+    /// Fields this crate doesn't know about yet
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl TokenLogprob {
+    pub fn probability(&self) -> f64 {
+        self.logprob.exp()
+    }
+
+    /// The most likely token other than the sampled one
+    pub fn best_alternative(&self) -> Option<&TopLogprob> {
+        self.top_logprobs
+            .iter()
+            .filter(|top| top.token != self.token)
+            .max_by(|a, b| a.logprob.total_cmp(&b.logprob))
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct TopLogprob {
+    pub token: String,
+    pub logprob: f64,
+    pub bytes: Option<Vec<u8>>,
+    /// Fields this crate doesn't know about yet
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl TopLogprob {
+    pub fn probability(&self) -> f64 {
+        self.logprob.exp()
+    }
+}
+
+/// exp of the negative mean logprob, or `None` without any token
+fn perplexity(logprobs: impl Iterator<Item = f64>) -> Option<f64> {
+    let (count, sum) = logprobs.fold((0, 0.0), |(count, sum), logprob| (count + 1, sum + logprob));
+
+    (count > 0).then(|| (-sum / count as f64).exp())
 }
 
 #[derive(Deserialize, Debug)]
 pub struct Completion {
     pub id: String,
     pub object: String,
-    #[serde(with = "ts_seconds_option")]
+    #[serde(with = "ts_seconds_option", default)]
     pub created: Option<DateTime<Utc>>,
     pub model: String,
     pub choices: Vec<Choice>,
-    pub usage: Usage,
+    #[serde(default)]
+    pub usage: Option<Usage>,
+    /// Fields this crate doesn't know about yet
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Completion {
+    /// Whether any choice was cut off by `max_tokens`
+    pub fn is_truncated(&self) -> bool {
+        self.choices
+            .iter()
+            .any(|choice| choice.finish_reason == Some(FinishReason::Length))
+    }
+}
+
+/// One page of a cursor-paginated list endpoint
+///
+/// Pass `last_id` as the `after` cursor of the next request while `has_more` is true, or use
+/// one of the `list_all_*` methods of `Client` to walk every page lazily
+#[derive(Deserialize, Debug)]
+pub struct Page<T> {
+    pub object: String,
+    pub data: Vec<T>,
+    pub first_id: Option<String>,
+    pub last_id: Option<String>,
+    #[serde(default)]
+    pub has_more: bool,
+    /// Fields this crate doesn't know about yet
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl<T> Page<T> {
+    /// The params that fetch the page following this one, if there is one
+    ///
+    /// Pages fetched with a `before` cursor are followed backwards, with `before` set to the first
+    /// id of this page
+    pub fn next_page_params(&self, params: &ListParams) -> Option<ListParams> {
+        if !self.has_more {
+            return None;
+        }
+
+        if params.before.is_some() {
+            self.first_id.as_ref().map(|first_id| ListParams {
+                after: None,
+                before: Some(first_id.clone()),
+                ..params.clone()
+            })
+        } else {
+            self.last_id.as_ref().map(|last_id| ListParams {
+                after: Some(last_id.clone()),
+                before: None,
+                ..params.clone()
+            })
+        }
+    }
+
+    /// Takes the missing `first_id` and `last_id` from the items, for list endpoints that don't
+    /// return them
+    pub(crate) fn fill_cursors(&mut self, id: fn(&T) -> &str) {
+        if self.first_id.is_none() {
+            self.first_id = self.data.first().map(|item| String::from(id(item)));
+        }
+        if self.last_id.is_none() {
+            self.last_id = self.data.last().map(|item| String::from(id(item)));
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DeletionStatus {
+    pub id: String,
+    pub object: String,
+    pub deleted: bool,
+    /// Fields this crate doesn't know about yet
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A function call requested by the model
+///
+/// Also serializable, since tool calls are sent back to the API as part of the conversation
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ToolCall {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub function: FunctionCall,
+    /// Fields this crate doesn't know about yet
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl ToolCall {
+    /// A call of the named function with JSON encoded arguments
+    pub fn function(id: String, name: String, arguments: String) -> Self {
+        ToolCall {
+            id,
+            kind: String::from("function"),
+            function: FunctionCall {
+                name,
+                arguments,
+                extra: Map::new(),
+            },
+            extra: Map::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FunctionCall {
+    pub name: String,
+    /// JSON encoded arguments, as generated by the model. These are not guaranteed to be valid JSON
+    pub arguments: String,
+    /// Fields this crate doesn't know about yet
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-// TODO
 #[derive(Deserialize, Debug)]
-pub struct ChatCompletion {}
+pub struct ChatChoice {
+    pub index: usize,
+    pub message: Message,
+    /// Only present when `logprobs` is requested
+    #[serde(default)]
+    pub logprobs: Option<ChatLogprobs>,
+    #[serde(default)]
+    pub finish_reason: Option<FinishReason>,
+    /// Fields this crate doesn't know about yet
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// ChatCompletion
+/// {
+/// "id": "chatcmpl-123",
+/// "object": "chat.completion",
+/// "created": 1677652288,
+/// "model": "gpt-4o-mini",
+/// "choices": [...],
+/// "usage": {...}
+/// }
+#[derive(Deserialize, Debug)]
+pub struct ChatCompletion {
+    pub id: String,
+    pub object: String,
+    #[serde(with = "ts_seconds_option", default)]
+    pub created: Option<DateTime<Utc>>,
+    pub model: String,
+    #[serde(default)]
+    pub system_fingerprint: Option<String>,
+    pub choices: Vec<ChatChoice>,
+    #[serde(default)]
+    pub usage: Option<Usage>,
+    /// Fields this crate doesn't know about yet
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl ChatCompletion {
+    /// Whether any choice was cut off by `max_tokens`
+    pub fn is_truncated(&self) -> bool {
+        self.choices
+            .iter()
+            .any(|choice| choice.finish_reason == Some(FinishReason::Length))
+    }
+}
+
+/// A chat completion whose reply was deserialized into the requested structured output type
+#[derive(Debug)]
+pub struct ParsedChatCompletion<T> {
+    pub parsed: T,
+    pub completion: ChatCompletion,
+}
 
 // TODO
 #[derive(Deserialize, Debug)]
-pub struct EditedPrompt {}
+pub struct EditedPrompt {
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
 
 #[cfg(test)]
 mod tests {
@@ -104,6 +569,149 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_usage_details_and_sum() {
+        let usage_data = r#"
+    {
+        "prompt_tokens": 131072,
+        "completion_tokens": 300,
+        "total_tokens": 131372,
+        "prompt_tokens_details": {"cached_tokens": 1024, "audio_tokens": 0},
+        "completion_tokens_details": {
+            "reasoning_tokens": 256,
+            "audio_tokens": 0,
+            "accepted_prediction_tokens": 0,
+            "rejected_prediction_tokens": 0
+        }
+    }
+        "#;
+
+        let long_usage: Usage = serde_json::from_str(usage_data).unwrap();
+        let short_usage = Usage {
+            prompt_tokens: 5,
+            completion_tokens: 7,
+            total_tokens: 12,
+            ..Default::default()
+        };
+
+        let total: Usage = [long_usage, short_usage].iter().sum();
+        assert_eq!(total.prompt_tokens, 131077);
+        assert_eq!(total.total_tokens, 131384);
+        assert_eq!(total.prompt_tokens_details.unwrap().cached_tokens, 1024);
+        assert_eq!(
+            total.completion_tokens_details.unwrap().reasoning_tokens,
+            256
+        );
+    }
+
+    #[test]
+    fn test_page_next_params() {
+        let page_data = r#"
+        {
+            "object": "list",
+            "data": [{ "id": "a" }, { "id": "b" }],
+            "first_id": "a",
+            "last_id": "b",
+            "has_more": true
+        }"#;
+
+        let page: Page<serde_json::Value> = serde_json::from_str(page_data).unwrap();
+        let params = ListParams {
+            limit: Some(2),
+            after: Some(String::from("z")),
+            ..Default::default()
+        };
+
+        let next = page.next_page_params(&params).unwrap();
+        assert_eq!(next.after, Some(String::from("b")));
+        assert_eq!(next.before, None);
+        assert_eq!(next.limit, Some(2));
+
+        let backwards = ListParams {
+            limit: Some(2),
+            before: Some(String::from("z")),
+            ..Default::default()
+        };
+        let previous = page.next_page_params(&backwards).unwrap();
+        assert_eq!(previous.before, Some(String::from("a")));
+        assert_eq!(previous.after, None);
+        assert_eq!(previous.limit, Some(2));
+
+        let last_page = Page::<serde_json::Value> {
+            has_more: false,
+            ..page
+        };
+        assert!(last_page.next_page_params(&next).is_none());
+    }
+
+    #[test]
+    fn test_page_cursors_from_items() {
+        let page_data = r#"
+        {
+            "object": "list",
+            "data": [{ "id": "a" }, { "id": "b" }],
+            "has_more": true
+        }"#;
+
+        let mut page: Page<serde_json::Value> = serde_json::from_str(page_data).unwrap();
+        assert!(page.next_page_params(&ListParams::default()).is_none());
+
+        page.fill_cursors(|item| item["id"].as_str().unwrap());
+        assert_eq!(page.first_id.as_deref(), Some("a"));
+        assert_eq!(
+            page.next_page_params(&ListParams::default()).unwrap().after,
+            Some(String::from("b"))
+        );
+    }
+
+    #[test]
+    fn test_chat_completion_tool_calls_deserialization() {
+        let chat_completion_data = r#"
+        {
+            "id": "chatcmpl-abc123",
+            "object": "chat.completion",
+            "created": 1699896916,
+            "model": "gpt-4o-mini",
+            "choices": [
+                {
+                    "index": 0,
+                    "message": {
+                        "role": "assistant",
+                        "content": null,
+                        "tool_calls": [
+                            {
+                                "id": "call_abc123",
+                                "type": "function",
+                                "function": {
+                                    "name": "get_weather",
+                                    "arguments": "{\"city\": \"Paris\"}"
+                                }
+                            }
+                        ]
+                    },
+                    "logprobs": null,
+                    "finish_reason": "tool_calls"
+                }
+            ],
+            "usage": {
+                "prompt_tokens": 82,
+                "completion_tokens": 17,
+                "total_tokens": 99
+            }
+        }"#;
+
+        let chat_completion: ChatCompletion = serde_json::from_str(chat_completion_data).unwrap();
+        let message = &chat_completion.choices[0].message;
+
+        assert_eq!(message.content, None);
+        assert_eq!(message.tool_calls()[0].id, "call_abc123");
+        assert_eq!(message.tool_calls()[0].function.name, "get_weather");
+        assert_eq!(
+            chat_completion.choices[0].finish_reason,
+            Some(FinishReason::ToolCalls)
+        );
+    }
+
     #[test]
     fn test_completion_deserialization() {
         let completion_data = r#"
@@ -131,8 +739,92 @@ mod tests {
             serde_json::from_str(completion_data).unwrap();
 
         assert_eq!(
-            deserialized_completion_data.choices.get(0).unwrap().text,
+            deserialized_completion_data.choices.first().unwrap().text,
             "\n\nThis is indeed a test"
         );
+        assert!(deserialized_completion_data.is_truncated());
+    }
+
+    #[test]
+    fn test_unknown_fields_are_kept() {
+        let model_data = r#"
+        {
+            "id": "gpt-4o-mini",
+            "object": "model",
+            "created": 1721172741,
+            "owned_by": "system",
+            "capabilities": {"vision": true}
+        }
+        "#;
+
+        let model: Model = serde_json::from_str(model_data).unwrap();
+
+        assert_eq!(model.root, None);
+        assert!(model.permission.is_empty());
+        assert_eq!(
+            model.extra.get("capabilities"),
+            Some(&serde_json::json!({"vision": true}))
+        );
+    }
+
+    #[test]
+    fn test_finish_reason_deserialization() {
+        let reasons: Vec<Option<FinishReason>> =
+            serde_json::from_str(r#"["length", "content_filter", "eos", null]"#).unwrap();
+
+        assert_eq!(
+            reasons,
+            vec![
+                Some(FinishReason::Length),
+                Some(FinishReason::ContentFilter),
+                Some(FinishReason::Other(String::from("eos"))),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_completion_logprobs_deserialization() {
+        let logprobs_data = r#"
+        {
+            "tokens": [" test", "."],
+            "token_logprobs": [-0.5, -1.5],
+            "top_logprobs": [{" test": -0.5, " trial": -3.5}, {".": -1.5, "!": -0.25}],
+            "text_offset": [10, 15]
+        }
+        "#;
+
+        let logprobs: Logprobs = serde_json::from_str(logprobs_data).unwrap();
+
+        assert_eq!(logprobs.perplexity(), Some(1f64.exp()));
+        assert_eq!(logprobs.probabilities()[0], Some((-0.5f64).exp()));
+        assert_eq!(
+            logprobs.best_alternatives(),
+            vec![Some((" trial", -3.5)), Some(("!", -0.25))]
+        );
+    }
+
+    #[test]
+    fn test_chat_logprobs_deserialization() {
+        let logprobs_data = r#"
+        {
+            "content": [{
+                "token": "Hello",
+                "logprob": -0.25,
+                "bytes": [72, 101, 108, 108, 111],
+                "top_logprobs": [
+                    {"token": "Hello", "logprob": -0.25, "bytes": [72, 101, 108, 108, 111]},
+                    {"token": "Hi", "logprob": -1.75, "bytes": [72, 105]}
+                ]
+            }],
+            "refusal": null
+        }
+        "#;
+
+        let logprobs: ChatLogprobs = serde_json::from_str(logprobs_data).unwrap();
+        let token = &logprobs.content.as_ref().unwrap()[0];
+
+        assert_eq!(token.best_alternative().unwrap().token, "Hi");
+        assert_eq!(logprobs.perplexity(), Some(0.25f64.exp()));
     }
 }
@@ -0,0 +1,414 @@
+use crate::construct::ChatCompletion;
+use crate::error::{OpenAIError, OpenAIResult};
+use crate::param::{ChatParams, Message, OptChatParams, Role};
+use crate::Client;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// The instructions given to the model when it is asked to summarise the oldest turns
+static SUMMARY_PROMPT: &str = "Summarise the following conversation in a few sentences. \
+Keep every fact, name and decision that later messages may refer to.";
+
+/// How a `Conversation` makes room once its history outgrows the token budget
+///
+/// The system prompt and the newest turn are always kept
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Truncation {
+    /// Drops the oldest turns, one at a time, until the history fits
+    #[default]
+    DropOldest,
+    /// Drops every turn but the newest, keeping only the system prompt
+    KeepSystemPrompt,
+    /// Replaces the older turns with a summary written by the model. When the summary doesn't
+    /// leave enough room either, the oldest turns are dropped
+    Summarize,
+}
+
+/// A chat history that grows with every exchange, and can be saved and resumed later
+///
+/// A turn is a user message along with the assistant and tool messages that follow it
+///
+/// ```no_run
+/// use openai_rust::conversation::{Conversation, Truncation};
+/// use openai_rust::Client;
+///
+/// let client = Client::new(String::from("sk-..."));
+/// let mut conversation = Conversation::new("gpt-4o-mini")
+///     .with_system_prompt("You are a terse assistant.")
+///     .with_token_budget(4_000, Truncation::DropOldest);
+///
+/// conversation.send(&client, "Name a prime number.").unwrap();
+/// conversation.send(&client, "And the next one?").unwrap();
+/// conversation.save("conversation.json").unwrap();
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Conversation {
+    pub model: String,
+    pub system_prompt: Option<String>,
+    /// Every message after the system prompt, oldest first
+    pub messages: Vec<Message>,
+    /// The most prompt tokens the history may take up, unlimited when `None`
+    #[serde(default)]
+    pub token_budget: Option<usize>,
+    #[serde(default)]
+    pub truncation: Truncation,
+    /// Sent along with every request
+    #[serde(default)]
+    pub opt: OptChatParams,
+}
+
+impl Conversation {
+    pub fn new(model: impl Into<String>) -> Self {
+        Conversation {
+            model: model.into(),
+            system_prompt: None,
+            messages: Vec::new(),
+            token_budget: None,
+            truncation: Truncation::default(),
+            opt: OptChatParams::default(),
+        }
+    }
+
+    pub fn with_system_prompt(mut self, system_prompt: impl Into<String>) -> Self {
+        self.system_prompt = Some(system_prompt.into());
+        self
+    }
+
+    /// Keeps the prompt tokens of the history within `token_budget`, making room with `truncation`
+    pub fn with_token_budget(mut self, token_budget: usize, truncation: Truncation) -> Self {
+        self.token_budget = Some(token_budget);
+        self.truncation = truncation;
+        self
+    }
+
+    /// The request for the whole history, starting with the system prompt
+    pub fn chat_params(&self) -> ChatParams {
+        let system_prompt = self.system_prompt.clone().map(Message::system);
+
+        ChatParams {
+            model: self.model.clone(),
+            messages: system_prompt
+                .into_iter()
+                .chain(self.messages.iter().cloned())
+                .collect(),
+            opt: self.opt.clone(),
+        }
+    }
+
+    /// Counts the prompt tokens of the whole history, see `ChatParams::count_prompt_tokens`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _the model isn't known to this crate_
+    pub fn count_tokens(&self) -> OpenAIResult<usize> {
+        self.chat_params().count_prompt_tokens()
+    }
+
+    /// Adds a user turn, fits the history within the token budget and appends the reply
+    ///
+    /// The history is left untouched if any request fails, so that the message can be sent again
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _the model isn't known to this crate_
+    /// * _the newest turn alone doesn't fit the token budget_
+    /// * _the chat completion or summary request fails_
+    /// * _the model calls tools instead of replying, see `OpenAIError::UnhandledToolCalls`_
+    #[tokio::main]
+    pub async fn send(
+        &mut self,
+        client: &Client,
+        text: impl Into<String>,
+    ) -> OpenAIResult<ChatCompletion> {
+        let mut next = self.clone();
+        next.messages.push(Message::user(text.into()));
+
+        let completion = next.exchange(client).await?;
+        *self = next;
+
+        Ok(completion)
+    }
+
+    async fn exchange(&mut self, client: &Client) -> OpenAIResult<ChatCompletion> {
+        self.truncate(client).await?;
+
+        let completion = client.chat_completion(&self.chat_params()).await?;
+        match completion.choices.first() {
+            // The API expects tool messages right after tool calls, so they can't be kept as a reply
+            Some(choice) if !choice.message.tool_calls().is_empty() => {
+                return Err(OpenAIError::UnhandledToolCalls(
+                    choice.message.tool_calls().to_vec(),
+                ))
+            }
+            Some(choice) => self.messages.push(choice.message.clone()),
+            None => return Err(OpenAIError::UnknownError),
+        }
+
+        Ok(completion)
+    }
+
+    /// Makes room in the history until it fits the token budget
+    async fn truncate(&mut self, client: &Client) -> OpenAIResult<()> {
+        let Some(token_budget) = self.token_budget else {
+            return Ok(());
+        };
+
+        loop {
+            let tokens = self.count_tokens()?;
+            if tokens <= token_budget {
+                return Ok(());
+            }
+            let newest_turn = self.newest_turn_start();
+            if newest_turn == 0 {
+                return Err(OpenAIError::ContextLengthExceeded {
+                    prompt_tokens: tokens,
+                    context_window: token_budget,
+                });
+            }
+
+            match self.truncation {
+                Truncation::KeepSystemPrompt => {
+                    self.messages.drain(..newest_turn);
+                }
+                // A lone message before the newest turn is a summary already
+                Truncation::Summarize if newest_turn > 1 => {
+                    let summary = self.summarize(client, newest_turn).await?;
+                    self.messages.splice(..newest_turn, [summary]);
+                }
+                _ => {
+                    let second_turn = self.second_turn_start().unwrap_or(newest_turn);
+                    self.messages.drain(..second_turn);
+                }
+            }
+        }
+    }
+
+    /// Asks the model to summarise the messages before `end`
+    async fn summarize(&self, client: &Client, end: usize) -> OpenAIResult<Message> {
+        let transcript = self.messages[..end]
+            .iter()
+            .map(|message| format!("{}: {}", message.role.as_str(), message.text()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let chat_params = ChatParams {
+            model: self.model.clone(),
+            messages: vec![
+                Message::system(String::from(SUMMARY_PROMPT)),
+                Message::user(transcript),
+            ],
+            // Keeps the spend of the summary attributed to the end user
+            opt: OptChatParams {
+                user: self.opt.user.clone(),
+                ..Default::default()
+            },
+        };
+
+        let completion = client.chat_completion(&chat_params).await?;
+        match completion.choices.first() {
+            Some(choice) => Ok(Message::system(format!(
+                "Summary of the earlier conversation: {}",
+                choice.message.text()
+            ))),
+            None => Err(OpenAIError::UnknownError),
+        }
+    }
+
+    /// Where the newest turn starts in `messages`
+    fn newest_turn_start(&self) -> usize {
+        self.messages
+            .iter()
+            .rposition(|message| message.role == Role::User)
+            .unwrap_or(0)
+    }
+
+    /// Where the turn after the oldest one starts in `messages`
+    fn second_turn_start(&self) -> Option<usize> {
+        self.messages
+            .iter()
+            .skip(1)
+            .position(|message| message.role == Role::User)
+            .map(|position| position + 1)
+    }
+
+    /// Writes the conversation to a JSON file
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _the file can't be written_
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> OpenAIResult<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+
+        Ok(())
+    }
+
+    /// Reads a conversation written by `save`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _the file can't be read_
+    /// * _the file isn't a saved conversation_
+    pub fn load<P: AsRef<Path>>(path: P) -> OpenAIResult<Self> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pricing::SpendTracker;
+    use crate::stand_in::stand_in_server;
+
+    fn conversation(turns: usize, truncation: Truncation) -> Conversation {
+        let mut conversation = Conversation::new("gpt-4o")
+            .with_system_prompt("You are a helpful assistant.")
+            .with_token_budget(60, truncation);
+        for turn in 0..turns {
+            conversation
+                .messages
+                .push(Message::user(format!("Question number {}", turn)));
+            conversation
+                .messages
+                .push(Message::assistant(format!("Answer number {}", turn)));
+        }
+        conversation
+            .messages
+            .push(Message::user(String::from("Last question")));
+
+        conversation
+    }
+
+    #[tokio::test]
+    async fn test_truncation_keeps_newest_turn() {
+        let client = Client::new(String::from("keystring"));
+
+        let mut dropped = conversation(10, Truncation::DropOldest);
+        dropped.truncate(&client).await.unwrap();
+        assert!(dropped.count_tokens().unwrap() <= 60);
+        assert!(dropped.messages.len() > 1);
+        assert_eq!(dropped.messages[0].role, Role::User);
+        assert_eq!(dropped.messages.last().unwrap().text(), "Last question");
+
+        let mut reset = conversation(10, Truncation::KeepSystemPrompt);
+        reset.truncate(&client).await.unwrap();
+        assert_eq!(
+            reset.messages,
+            vec![Message::user(String::from("Last question"))]
+        );
+        assert_eq!(
+            reset.chat_params().messages[0],
+            Message::system(String::from("You are a helpful assistant."))
+        );
+
+        let mut oversized = conversation(0, Truncation::DropOldest);
+        oversized.token_budget = Some(10);
+        assert!(matches!(
+            oversized.truncate(&client).await,
+            Err(OpenAIError::ContextLengthExceeded {
+                context_window: 10,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_summarize_replaces_older_turns() {
+        let api_base = stand_in_server(|request| {
+            assert!(request.contains(r#""user":"alice""#));
+            String::from(
+                r#"{"id":"chatcmpl-1","object":"chat.completion","model":"gpt-4o","choices":[{"index":0,"message":{"role":"assistant","content":"Ten questions were answered."},"finish_reason":"stop"}],"usage":{"prompt_tokens":100,"completion_tokens":10,"total_tokens":110}}"#,
+            )
+        });
+        let spend_tracker = SpendTracker::new();
+        let client = Client::new(String::from("keystring"))
+            .with_api_base(api_base)
+            .with_spend_tracker(spend_tracker.clone());
+
+        let mut summarized = conversation(10, Truncation::Summarize);
+        summarized.opt.user = Some(String::from("alice"));
+        summarized.truncate(&client).await.unwrap();
+        assert!(spend_tracker.by_user()["alice"] > 0.0);
+        assert_eq!(
+            summarized.messages,
+            vec![
+                Message::system(String::from(
+                    "Summary of the earlier conversation: Ten questions were answered."
+                )),
+                Message::user(String::from("Last question")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tool_calls_are_not_kept_as_replies() {
+        let api_base = stand_in_server(|_| {
+            String::from(
+                r#"{"id":"chatcmpl-1","object":"chat.completion","model":"gpt-4o","choices":[{"index":0,"message":{"role":"assistant","content":null,"tool_calls":[{"id":"call_1","type":"function","function":{"name":"get_time","arguments":"{}"}}]},"finish_reason":"tool_calls"}]}"#,
+            )
+        });
+        let client = Client::new(String::from("keystring")).with_api_base(api_base);
+
+        let mut conversation = Conversation::new("gpt-4o");
+        let result = conversation.send(&client, "What time is it?");
+
+        let Err(OpenAIError::UnhandledToolCalls(tool_calls)) = result else {
+            panic!("expected UnhandledToolCalls, got {:?}", result);
+        };
+        assert_eq!(tool_calls[0].function.name, "get_time");
+        assert!(conversation.messages.is_empty());
+    }
+
+    #[test]
+    fn test_failed_send_keeps_history() {
+        // Nothing listens on the port once the listener is dropped
+        let api_base = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}/v1", listener.local_addr().unwrap())
+        };
+        let client = Client::new(String::from("keystring")).with_api_base(api_base);
+
+        let mut conversation = conversation(10, Truncation::DropOldest);
+        let messages = conversation.messages.clone();
+        assert!(conversation.send(&client, "Another question").is_err());
+        assert_eq!(conversation.messages, messages);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let conversation = conversation(2, Truncation::KeepSystemPrompt);
+        let path = std::env::temp_dir().join("openai-rust-test-conversation.json");
+
+        conversation.save(&path).unwrap();
+        let loaded = Conversation::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.model, conversation.model);
+        assert_eq!(loaded.messages, conversation.messages);
+        assert_eq!(loaded.truncation, Truncation::KeepSystemPrompt);
+        assert!(matches!(
+            Conversation::load(std::env::temp_dir().join("openai-rust-missing.json")),
+            Err(OpenAIError::IoError(_))
+        ));
+    }
+
+    #[test]
+    fn test_conversation_json_round_trip() {
+        let conversation = conversation(2, Truncation::Summarize);
+
+        let json = serde_json::to_string(&conversation).unwrap();
+        let loaded: Conversation = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.messages, conversation.messages);
+        assert_eq!(loaded.system_prompt, conversation.system_prompt);
+        assert_eq!(loaded.truncation, Truncation::Summarize);
+        assert_eq!(loaded.token_budget, Some(60));
+
+        let minimal: Conversation =
+            serde_json::from_str(r#"{"model":"gpt-4o","system_prompt":null,"messages":[]}"#)
+                .unwrap();
+        assert_eq!(minimal.truncation, Truncation::DropOldest);
+    }
+}
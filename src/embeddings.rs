@@ -0,0 +1,130 @@
+use crate::construct::Usage;
+use crate::error::OpenAIResult;
+use crate::param::Prompt;
+use crate::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EmbeddingParams {
+    pub model: String,
+    /// The text or tokens to embed, a batch gets one embedding per prompt
+    pub input: Prompt,
+    #[serde(flatten)]
+    pub opts: OptEmbeddingParams,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct OptEmbeddingParams {
+    /// The number of dimensions of the embeddings, only supported by `text-embedding-3` models
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dimensions: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Embeddings {
+    pub object: String,
+    pub data: Vec<Embedding>,
+    pub model: String,
+    pub usage: Option<EmbeddingUsage>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Embedding {
+    pub object: String,
+    /// The position of the embedded prompt in the input
+    pub index: usize,
+    pub embedding: Vec<f32>,
+}
+
+/// Embeddings only count the tokens of the input
+#[derive(Deserialize, Debug, Default)]
+pub struct EmbeddingUsage {
+    pub prompt_tokens: u32,
+    pub total_tokens: u32,
+}
+
+impl From<&EmbeddingUsage> for Usage {
+    fn from(usage: &EmbeddingUsage) -> Self {
+        Usage {
+            prompt_tokens: usage.prompt_tokens as u64,
+            total_tokens: usage.total_tokens as u64,
+            ..Usage::default()
+        }
+    }
+}
+
+impl Client {
+    /// Creates an embedding vector for each prompt of the input
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _the budget of the spend tracker is spent_
+    /// * _the requested model doesn't exist_
+    /// * _endpoint is unavailable_
+    /// * _deserialization of JSON Response data fails_
+    #[tokio::main]
+    pub async fn create_embeddings(&self, params: EmbeddingParams) -> OpenAIResult<Embeddings> {
+        self.check_budget(Some(&params.model))?;
+        let url = format!("{}/embeddings", self.api_base);
+
+        let embeddings: Embeddings = self.send(self.http_client.post(url).json(&params)).await?;
+        self.record_spend(
+            &embeddings.model,
+            params.opts.user.as_deref(),
+            embeddings.usage.as_ref().map(Usage::from).as_ref(),
+        );
+
+        Ok(embeddings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pricing::SpendTracker;
+
+    #[test]
+    fn test_create_embeddings_records_spend() {
+        let api_base = crate::stand_in::stand_in_server(|request| {
+            assert!(request.starts_with("POST /v1/embeddings"));
+            assert!(request.contains(r#""input":["Hello","World"]"#));
+            String::from(
+                r#"{
+                    "object": "list",
+                    "data": [
+                        {"object": "embedding", "index": 0, "embedding": [0.1, -0.2]},
+                        {"object": "embedding", "index": 1, "embedding": [0.3, 0.4]}
+                    ],
+                    "model": "text-embedding-3-small",
+                    "usage": {"prompt_tokens": 1000000, "total_tokens": 1000000}
+                }"#,
+            )
+        });
+        let spend_tracker = SpendTracker::new();
+        let client = Client::new(String::from("keystring"))
+            .with_api_base(api_base)
+            .with_spend_tracker(spend_tracker.clone());
+
+        let embeddings = client
+            .create_embeddings(EmbeddingParams {
+                model: String::from("text-embedding-3-small"),
+                input: Prompt::TextBatch(vec![String::from("Hello"), String::from("World")]),
+                opts: OptEmbeddingParams {
+                    user: Some(String::from("alice")),
+                    ..Default::default()
+                },
+            })
+            .unwrap();
+
+        assert_eq!(embeddings.data.len(), 2);
+        assert_eq!(embeddings.data[1].embedding, vec![0.3, 0.4]);
+        assert!((embeddings.estimated_cost().unwrap() - 0.02).abs() < 1e-9);
+        assert!((spend_tracker.by_user()["alice"] - 0.02).abs() < 1e-9);
+    }
+}
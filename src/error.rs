@@ -1,3 +1,6 @@
+use crate::construct::{FinishReason, ToolCall};
+use crate::param::Message;
+use reqwest::{Response, StatusCode};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -10,6 +13,24 @@ pub fn construct_error_msg(status_code: String, err_data: APIErrorData) -> Strin
     )
 }
 
+/// Converts an unsuccessful API response into the matching `OpenAIError`
+pub(crate) async fn from_response(resp: Response) -> OpenAIError {
+    let err_code = resp.status();
+    let err_data: APIErrorData = match resp.json::<APIError>().await {
+        Ok(api_error) => api_error.into(),
+        Err(e) => return e.into(),
+    };
+    let err_msg = construct_error_msg(err_code.to_string(), err_data);
+
+    match err_code {
+        StatusCode::NOT_FOUND => OpenAIError::InternalAPIError(err_msg),
+        StatusCode::UNAUTHORIZED => OpenAIError::AuthenticationError(err_msg),
+        StatusCode::TOO_MANY_REQUESTS => OpenAIError::RateLimitError(err_msg),
+        StatusCode::INTERNAL_SERVER_ERROR => OpenAIError::ServerError(err_msg),
+        _ => OpenAIError::OtherError(err_msg),
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum OpenAIError {
     #[error("Internal API Error: {0}")]
@@ -26,10 +47,84 @@ pub enum OpenAIError {
     RequestError(#[from] reqwest::Error),
     #[error("Unable to parse response into valid JSON: {0}")]
     ParseError(#[from] serde_json::Error),
+    #[error("WebSocket Error: {0}")]
+    WebSocketError(Box<tokio_tungstenite::tungstenite::Error>),
+    #[error("Unable to build params: {0}")]
+    BuilderError(String),
+    /// Lists every constraint the params violate, found before sending the request
+    #[error("Invalid params: {}", .0.join("; "))]
+    InvalidParams(Vec<String>),
+    #[error("IO Error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Unsupported image type: {0}")]
+    UnsupportedImageType(String),
+    /// The image couldn't be decoded or encoded, or downscaling was asked for without the `image`
+    /// feature
+    #[error("Image Error: {0}")]
+    ImageError(String),
+    #[error("The output was cut off by max_tokens or the context length")]
+    Truncated,
+    #[error("The model refused to answer: {0}")]
+    Refusal(String),
+    /// The reply doesn't match the requested structured output type
+    #[error("Unable to parse structured output: {0}")]
+    StructuredOutputError(serde_json::Error),
+    /// Returned by `ToolRunner` when the model stops without a final answer, e.g. because of the
+    /// content filter. A reply cut off by `max_tokens` fails with `Truncated` instead
+    #[error("The model stopped with an unexpected finish reason: {0:?}")]
+    UnexpectedFinishReason(Option<FinishReason>),
+    /// Returned by `Conversation` when the model calls tools instead of replying. Use `ToolRunner`
+    /// to run the tool calls of a conversation
+    #[error("The model called {} tools instead of replying", .0.len())]
+    UnhandledToolCalls(Vec<ToolCall>),
+    /// Returned by `ToolRunner` along with the conversation up to that point
+    #[error("Tool calls were still pending after {iterations} iterations")]
+    MaxIterationsReached {
+        iterations: usize,
+        transcript: Vec<Message>,
+    },
+    #[error("Unknown model: {0}")]
+    UnknownModel(String),
+    /// The prompt takes up the whole context window, so there's no room for the completion
+    #[error("The prompt is {prompt_tokens} tokens long, which doesn't fit the context window of {context_window} tokens")]
+    ContextLengthExceeded {
+        prompt_tokens: usize,
+        context_window: usize,
+    },
+    /// Returned by a `Client` whose `SpendTracker` is over its budget, before sending the request
+    #[error("Spent ${spent:.4} of the ${budget:.2} budget")]
+    BudgetExceeded { spent: f64, budget: f64 },
+    /// The model has no price, so its spend couldn't be counted against the budget
+    #[error("No price is known for model {0}, so it can't be used within a budget")]
+    UnpricedModel(String),
+    #[error("Invalid template: {0}")]
+    TemplateError(String),
+    /// Lists every variable a template needs that wasn't supplied
+    #[error("Missing template variables: {}", .0.join(", "))]
+    MissingVariables(Vec<String>),
+    #[error("Token {0} isn't part of the encoding")]
+    InvalidToken(u32),
+    /// A run or vector store ingestion was still pending when the poll timeout of the `Client`
+    /// passed
+    #[error("Still pending after polling for {0:?}")]
+    PollTimeout(std::time::Duration),
     #[error("Unknown Error happened")]
     UnknownError,
 }
 
+impl From<tokio_tungstenite::tungstenite::Error> for OpenAIError {
+    fn from(value: tokio_tungstenite::tungstenite::Error) -> Self {
+        OpenAIError::WebSocketError(Box::new(value))
+    }
+}
+
+#[cfg(feature = "image")]
+impl From<image::ImageError> for OpenAIError {
+    fn from(value: image::ImageError) -> Self {
+        OpenAIError::ImageError(value.to_string())
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct APIError {
     pub error: APIErrorData,
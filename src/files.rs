@@ -0,0 +1,123 @@
+use crate::construct::Page;
+use crate::error::OpenAIResult;
+use crate::param::ListParams;
+use crate::Client;
+use chrono::serde::ts_seconds_option;
+use chrono::{DateTime, Utc};
+use futures::Stream;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// FileObject
+/// {
+/// "id": "file-abc123",
+/// "object": "file",
+/// "bytes": 120000,
+/// "created_at": 1677610602,
+/// "filename": "salesOverview.pdf",
+/// "purpose": "assistants"
+/// }
+#[derive(Deserialize, Debug)]
+pub struct FileObject {
+    pub id: String,
+    pub object: String,
+    pub bytes: u64,
+    #[serde(with = "ts_seconds_option")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, with = "ts_seconds_option")]
+    pub expires_at: Option<DateTime<Utc>>,
+    pub filename: String,
+    pub purpose: String,
+    /// Fields this crate doesn't know about yet
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Client {
+    fn files_url(&self) -> String {
+        format!("{}/files", self.api_base)
+    }
+}
+
+impl Client {
+    /// Lists the uploaded files, only those uploaded for `purpose` if given
+    #[tokio::main]
+    pub async fn list_files(
+        &self,
+        purpose: Option<&str>,
+        params: ListParams,
+    ) -> OpenAIResult<Page<FileObject>> {
+        self.send(
+            self.http_client
+                .get(self.files_url())
+                .query(&params)
+                .query(&[("purpose", purpose)]),
+        )
+        .await
+    }
+
+    /// Lazily walks every uploaded file, only those uploaded for `purpose` if given, fetching
+    /// further pages as needed
+    ///
+    /// The stream has to be polled inside a Tokio runtime
+    pub fn list_all_files<'a>(
+        &'a self,
+        purpose: Option<&'a str>,
+        params: ListParams,
+    ) -> impl Stream<Item = OpenAIResult<FileObject>> + 'a {
+        self.paginate(
+            move |query| {
+                self.http_client
+                    .get(self.files_url())
+                    .query(query)
+                    .query(&[("purpose", purpose)])
+            },
+            params,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_deserialization() {
+        let file_data = r#"
+        {
+            "id": "file-abc123",
+            "object": "file",
+            "bytes": 120000,
+            "created_at": 1677610602,
+            "expires_at": null,
+            "filename": "salesOverview.pdf",
+            "purpose": "assistants"
+        }"#;
+
+        let file: FileObject = serde_json::from_str(file_data).unwrap();
+
+        assert_eq!(file.bytes, 120000);
+        assert_eq!(file.purpose, "assistants");
+        assert!(file.expires_at.is_none());
+    }
+
+    #[test]
+    fn test_purpose_filter_is_optional() {
+        let client = Client::new(String::from("keystring"));
+        let query = |purpose: Option<&str>| {
+            client
+                .http_client
+                .get(client.files_url())
+                .query(&ListParams::default())
+                .query(&[("purpose", purpose)])
+                .build()
+                .unwrap()
+                .url()
+                .query()
+                .map(String::from)
+        };
+
+        assert_eq!(query(None), None);
+        assert_eq!(query(Some("batch")).as_deref(), Some("purpose=batch"));
+    }
+}
@@ -0,0 +1,143 @@
+use crate::construct::Page;
+use crate::error::OpenAIResult;
+use crate::param::ListParams;
+use crate::Client;
+use chrono::serde::ts_seconds_option;
+use chrono::{DateTime, Utc};
+use futures::Stream;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// FineTuningJob
+/// {
+/// "id": "ftjob-abc123",
+/// "object": "fine_tuning.job",
+/// "model": "gpt-4o-mini-2024-07-18",
+/// "created_at": 1721764800,
+/// "fine_tuned_model": null,
+/// "status": "queued",
+/// "training_file": "file-abc123",
+/// ...
+/// }
+#[derive(Deserialize, Debug)]
+pub struct FineTuningJob {
+    pub id: String,
+    pub object: String,
+    pub model: String,
+    #[serde(with = "ts_seconds_option")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, with = "ts_seconds_option")]
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Set once the job succeeds
+    pub fine_tuned_model: Option<String>,
+    pub status: FineTuningJobStatus,
+    pub training_file: String,
+    pub validation_file: Option<String>,
+    #[serde(default)]
+    pub result_files: Vec<String>,
+    pub trained_tokens: Option<u64>,
+    pub error: Option<FineTuningError>,
+    pub metadata: Option<HashMap<String, String>>,
+    /// Fields this crate doesn't know about yet
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FineTuningJobStatus {
+    ValidatingFiles,
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct FineTuningError {
+    pub code: String,
+    pub message: String,
+    pub param: Option<String>,
+}
+
+impl Client {
+    fn fine_tuning_jobs_url(&self) -> String {
+        format!("{}/fine_tuning/jobs", self.api_base)
+    }
+}
+
+impl Client {
+    /// Lists the fine-tuning jobs. The endpoint only takes the `after` cursor and `limit`
+    #[tokio::main]
+    pub async fn list_fine_tuning_jobs(
+        &self,
+        params: ListParams,
+    ) -> OpenAIResult<Page<FineTuningJob>> {
+        self.send(
+            self.http_client
+                .get(self.fine_tuning_jobs_url())
+                .query(&params),
+        )
+        .await
+    }
+
+    /// Lazily walks every fine-tuning job, fetching further pages as needed
+    ///
+    /// The stream has to be polled inside a Tokio runtime
+    pub fn list_all_fine_tuning_jobs(
+        &self,
+        params: ListParams,
+    ) -> impl Stream<Item = OpenAIResult<FineTuningJob>> + '_ {
+        // The pages of this endpoint don't carry `first_id` and `last_id`
+        self.paginate_with_ids(
+            move |query| {
+                self.http_client
+                    .get(self.fine_tuning_jobs_url())
+                    .query(query)
+            },
+            params,
+            Some(|job: &FineTuningJob| &job.id),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fine_tuning_job_page_deserialization() {
+        let page_data = r#"
+        {
+            "object": "list",
+            "data": [
+                {
+                    "id": "ftjob-abc123",
+                    "object": "fine_tuning.job",
+                    "model": "gpt-4o-mini-2024-07-18",
+                    "created_at": 1721764800,
+                    "finished_at": null,
+                    "fine_tuned_model": null,
+                    "organization_id": "org-123",
+                    "result_files": [],
+                    "status": "queued",
+                    "validation_file": null,
+                    "training_file": "file-abc123",
+                    "error": null,
+                    "metadata": null
+                }
+            ],
+            "has_more": true
+        }"#;
+
+        let mut page: Page<FineTuningJob> = serde_json::from_str(page_data).unwrap();
+        assert_eq!(page.data[0].status, FineTuningJobStatus::Queued);
+        assert!(page.data[0].extra.contains_key("organization_id"));
+
+        page.fill_cursors(|job| &job.id);
+        let next = page.next_page_params(&ListParams::default()).unwrap();
+        assert_eq!(next.after, Some(String::from("ftjob-abc123")));
+    }
+}
@@ -1,18 +1,72 @@
+pub mod assistants;
+pub mod batches;
 pub mod construct;
+#[cfg(feature = "tokenizer")]
+pub mod conversation;
+pub mod embeddings;
 pub mod error;
+pub mod files;
+pub mod fine_tuning;
 pub mod param;
+pub mod pricing;
+pub mod realtime;
+pub mod responses;
+pub mod runner;
+pub mod runs;
+mod sse;
+#[cfg(test)]
+mod stand_in;
+pub mod template;
+pub mod threads;
+#[cfg(feature = "tokenizer")]
+pub mod tokenizer;
+#[cfg(feature = "schemars")]
+pub mod tools;
+pub mod vector_stores;
+pub mod vision;
 
-use construct::{ChatCompletion, Completion, EditedPrompt, Model, ModelList};
-use error::{construct_error_msg, APIError, APIErrorData, OpenAIError, OpenAIResult};
-use param::{ChatParams, CompletionParams, EditParams};
+use construct::{ChatCompletion, Completion, EditedPrompt, Model, ModelList, Page, Usage};
+use error::OpenAIResult;
+use futures::{Stream, TryStreamExt};
+use param::{ChatParams, CompletionParams, EditParams, ListParams};
+use pricing::SpendTracker;
 use reqwest::{
     self,
     header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE},
-    ClientBuilder, StatusCode,
+    ClientBuilder, Method, RequestBuilder, StatusCode,
 };
+use serde::{de::DeserializeOwned, Serialize};
+use sse::SseEvent;
+use std::future::Future;
+use std::time::Duration;
 
 static APP_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"),);
 
+pub(crate) static API_BASE: &str = "https://api.openai.com/v1";
+
+/// How long the `poll_*` methods wait for a run or an ingestion to finish by default
+const DEFAULT_POLL_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// The Assistants, Threads, Runs and Vector Stores endpoints are only served with this beta header
+static OPENAI_BETA: (&str, &str) = ("OpenAI-Beta", "assistants=v2");
+
+/// Wraps request params with `"stream": true` for endpoints that answer with server-sent events
+#[derive(Serialize)]
+pub(crate) struct Streaming<'a, T: Serialize> {
+    #[serde(flatten)]
+    params: &'a T,
+    stream: bool,
+}
+
+impl<'a, T: Serialize> Streaming<'a, T> {
+    pub(crate) fn new(params: &'a T) -> Self {
+        Streaming {
+            params,
+            stream: true,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Config {
     pub openai_secret_key: String,
@@ -23,6 +77,13 @@ pub struct Config {
 pub struct Client {
     pub config: Config,
     http_client: reqwest::Client,
+    api_base: String,
+    poll_timeout: Duration,
+    validate_params: bool,
+    error_on_truncation: bool,
+    #[cfg(feature = "tokenizer")]
+    fit_max_tokens: bool,
+    spend_tracker: Option<SpendTracker>,
 }
 
 impl Client {
@@ -51,6 +112,13 @@ impl Client {
         Client {
             config,
             http_client: client,
+            api_base: String::from(API_BASE),
+            poll_timeout: DEFAULT_POLL_TIMEOUT,
+            validate_params: true,
+            error_on_truncation: false,
+            #[cfg(feature = "tokenizer")]
+            fit_max_tokens: false,
+            spend_tracker: None,
         }
     }
 
@@ -61,6 +129,58 @@ impl Client {
         cl
     }
 
+    /// Sends requests to `api_base` instead of `https://api.openai.com/v1`, e.g. for a proxy or a
+    /// compatible server. Realtime sessions connect to the matching `ws://` or `wss://` URL
+    pub fn with_api_base(mut self, api_base: impl Into<String>) -> Self {
+        self.api_base = api_base.into();
+        self
+    }
+
+    /// Sets how long the `poll_*` and `*_and_poll` methods wait for a run, vector store file or
+    /// file batch to finish before failing with `OpenAIError::PollTimeout`. 10 minutes by default
+    pub fn with_poll_timeout(mut self, poll_timeout: Duration) -> Self {
+        self.poll_timeout = poll_timeout;
+        self
+    }
+
+    /// Turns the client-side checks of completion, chat and edit params on or off. They are on
+    /// by default
+    ///
+    /// Turning them off lets the API judge params on its own, e.g. for compatible servers with
+    /// other limits
+    pub fn with_validation(mut self, enabled: bool) -> Self {
+        self.validate_params = enabled;
+        self
+    }
+
+    /// Makes completions and chat completions cut off by `max_tokens` fail with
+    /// `OpenAIError::Truncated`, instead of returning the partial output. Off by default
+    pub fn with_truncation_error(mut self, enabled: bool) -> Self {
+        self.error_on_truncation = enabled;
+        self
+    }
+
+    /// Sets `max_tokens` of completions and chat completions so that the prompt and the
+    /// completion fit the context window of the model. Off by default
+    ///
+    /// A requested `max_tokens` is lowered if needed, and a missing one is filled with the room
+    /// left. Chat requests to reasoning models get `max_completion_tokens` instead, see
+    /// `ChatParams::fit_max_tokens`. Requests for models unknown to `tokenizer::ModelLimits`, or
+    /// whose prompt alone doesn't fit, fail before being sent
+    #[cfg(feature = "tokenizer")]
+    pub fn with_max_tokens_fitting(mut self, enabled: bool) -> Self {
+        self.fit_max_tokens = enabled;
+        self
+    }
+
+    /// Adds the estimated cost of every completion, chat completion, response and run to
+    /// `spend_tracker`, and rejects them once its budget is spent. While a budget is set, requests
+    /// for models without a known price are rejected as well
+    pub fn with_spend_tracker(mut self, spend_tracker: SpendTracker) -> Self {
+        self.spend_tracker = Some(spend_tracker);
+        self
+    }
+
     /// Lists the currently available models, and provides basic information about each one such as the owner and availability.
     ///
     /// The Model vector is accessible through the "data" field
@@ -72,24 +192,9 @@ impl Client {
     /// * _deserialization of JSON response data fails_
     #[tokio::main]
     pub async fn get_models(&self) -> OpenAIResult<ModelList> {
-        let model_url = String::from("https://api.openai.com/v1/models");
-        let resp = self.http_client.get(model_url.clone()).send().await?;
+        let model_url = format!("{}/models", self.api_base);
 
-        if resp.status() == StatusCode::OK {
-            Ok(resp.json::<ModelList>().await?)
-        } else {
-            let err_code = resp.status();
-            let err_data: APIErrorData = resp.json::<APIError>().await?.into();
-            let err_msg = construct_error_msg(err_code.to_string().clone(), err_data);
-
-            match err_code {
-                StatusCode::NOT_FOUND => Err(OpenAIError::InternalAPIError(err_msg)),
-                StatusCode::UNAUTHORIZED => Err(OpenAIError::AuthenticationError(err_msg)),
-                StatusCode::TOO_MANY_REQUESTS => Err(OpenAIError::RateLimitError(err_msg)),
-                StatusCode::INTERNAL_SERVER_ERROR => Err(OpenAIError::ServerError(err_msg)),
-                _ => Err(OpenAIError::OtherError(err_msg)),
-            }
-        }
+        self.send(self.http_client.get(model_url)).await
     }
 
     /// Retrieves a model instance, providing basic information about the model such as the owner and permissioning
@@ -102,26 +207,9 @@ impl Client {
     /// * _deserialization of JSON Model data fails_
     #[tokio::main]
     pub async fn get_model_info(&self, model: String) -> OpenAIResult<Model> {
-        let model_url = format!("https://api.openai.com/v1/models/{}", model);
-
-        // Break out Response into the sending request part and the parsing Json part
-        let resp = self.http_client.get(model_url.clone()).send().await?;
+        let model_url = format!("{}/models/{}", self.api_base, model);
 
-        if resp.status() == StatusCode::OK {
-            Ok(resp.json::<Model>().await?)
-        } else {
-            let err_code = resp.status();
-            let err_data: APIErrorData = resp.json::<APIError>().await?.into();
-            let err_msg = construct_error_msg(err_code.to_string().clone(), err_data);
-
-            match err_code {
-                StatusCode::NOT_FOUND => Err(OpenAIError::InternalAPIError(err_msg)),
-                StatusCode::UNAUTHORIZED => Err(OpenAIError::AuthenticationError(err_msg)),
-                StatusCode::TOO_MANY_REQUESTS => Err(OpenAIError::RateLimitError(err_msg)),
-                StatusCode::INTERNAL_SERVER_ERROR => Err(OpenAIError::ServerError(err_msg)),
-                _ => Err(OpenAIError::OtherError(err_msg)),
-            }
-        }
+        self.send(self.http_client.get(model_url)).await
     }
 
     /// Given a prompt, the model will return one or more predicted completions, and can also return the probabilities of alternative tokens at each position.
@@ -129,6 +217,10 @@ impl Client {
     /// # Errors
     ///
     /// This function will return an error if -
+    /// * _the params are invalid, see `CompletionParams::validate`_
+    /// * _the prompt doesn't fit the context window and `with_max_tokens_fitting` is on_
+    /// * _the budget of the spend tracker is spent_
+    /// * _the output is truncated and `with_truncation_error` is on_
     /// * _the requested model doesn't exist_
     /// * _endpoint is unavailable_
     /// * _deserialization of JSON Model data fails_
@@ -137,46 +229,281 @@ impl Client {
         &self,
         completion_params: CompletionParams,
     ) -> OpenAIResult<Completion> {
-        let completion_url = String::from("https://api.openai.com/v1/completions");
+        if self.validate_params {
+            completion_params.validate()?;
+        }
+        #[cfg(feature = "tokenizer")]
+        let completion_params = if self.fit_max_tokens {
+            let mut completion_params = completion_params;
+            completion_params.fit_max_tokens()?;
+            completion_params
+        } else {
+            completion_params
+        };
+        self.check_budget(Some(&completion_params.model))?;
+        let completion_url = format!("{}/completions", self.api_base);
 
         let completion_body = serde_json::to_string(&completion_params)?;
 
-        let resp = self
-            .http_client
-            .post(completion_url.clone())
-            .body(completion_body)
-            .send()
+        let completion: Completion = self
+            .send(self.http_client.post(completion_url).body(completion_body))
             .await?;
-
-        if resp.status() == StatusCode::OK {
-            Ok(resp.json::<Completion>().await?)
-        } else {
-            let err_code = resp.status();
-            let err_data: APIErrorData = resp.json::<APIError>().await?.into();
-            let err_msg = construct_error_msg(err_code.to_string().clone(), err_data);
-
-            match err_code {
-                StatusCode::NOT_FOUND => Err(OpenAIError::InternalAPIError(err_msg)),
-                StatusCode::UNAUTHORIZED => Err(OpenAIError::AuthenticationError(err_msg)),
-                StatusCode::TOO_MANY_REQUESTS => Err(OpenAIError::RateLimitError(err_msg)),
-                StatusCode::INTERNAL_SERVER_ERROR => Err(OpenAIError::ServerError(err_msg)),
-                _ => Err(OpenAIError::OtherError(err_msg)),
-            }
+        self.record_spend(
+            &completion.model,
+            completion_params.opts.user.as_deref(),
+            completion.usage.as_ref(),
+        );
+        if self.error_on_truncation && completion.is_truncated() {
+            return Err(error::OpenAIError::Truncated);
         }
+
+        Ok(completion)
     }
 
+    /// Given a list of messages comprising a conversation, the model will return a response.
+    ///
+    /// The response may contain tool calls instead of text if `tools` were given, answer them with `Message::tool` messages
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _the params are invalid, see `ChatParams::validate`_
+    /// * _the prompt doesn't fit the context window and `with_max_tokens_fitting` is on_
+    /// * _the budget of the spend tracker is spent_
+    /// * _the output is truncated and `with_truncation_error` is on_
+    /// * _the requested model doesn't exist_
+    /// * _endpoint is unavailable_
+    /// * _deserialization of JSON ChatCompletion data fails_
     #[tokio::main]
     pub async fn create_chat_completion(
         &self,
-        _chat_params: ChatParams,
+        chat_params: ChatParams,
     ) -> OpenAIResult<ChatCompletion> {
-        todo!()
+        self.chat_completion(&chat_params).await
+    }
+
+    /// Asks the model to answer with the JSON Schema of `T`, and deserializes its reply into `T`
+    ///
+    /// Any `response_format` already set on `chat_params` is replaced
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _the request fails like `create_chat_completion` would_
+    /// * _the model refuses to answer, as `OpenAIError::Refusal`_
+    /// * _the reply was cut off by `max_tokens`, as `OpenAIError::Truncated`_
+    /// * _the reply can't be deserialized into `T`, as `OpenAIError::StructuredOutputError`_
+    #[cfg(feature = "schemars")]
+    #[tokio::main]
+    pub async fn create_chat_completion_parsed<T>(
+        &self,
+        mut chat_params: ChatParams,
+    ) -> OpenAIResult<construct::ParsedChatCompletion<T>>
+    where
+        T: DeserializeOwned + schemars::JsonSchema,
+    {
+        chat_params.opt.response_format = Some(tools::response_format::<T>());
+
+        let completion = self.chat_completion(&chat_params).await?;
+        let message = match completion.choices.first() {
+            Some(choice) if choice.finish_reason == Some(construct::FinishReason::Length) => {
+                return Err(error::OpenAIError::Truncated)
+            }
+            Some(choice) => &choice.message,
+            None => return Err(error::OpenAIError::UnknownError),
+        };
+        if let Some(refusal) = &message.refusal {
+            return Err(error::OpenAIError::Refusal(refusal.clone()));
+        }
+        let parsed = serde_json::from_str(&message.text())
+            .map_err(error::OpenAIError::StructuredOutputError)?;
+
+        Ok(construct::ParsedChatCompletion { parsed, completion })
     }
 
     #[tokio::main]
     pub async fn edit_prompt(&self, _edit_params: EditParams) -> OpenAIResult<EditedPrompt> {
         todo!()
     }
+
+    /// Sends a chat completion request, for callers that are already running inside a runtime
+    pub(crate) async fn chat_completion(
+        &self,
+        chat_params: &ChatParams,
+    ) -> OpenAIResult<ChatCompletion> {
+        if self.validate_params {
+            chat_params.validate()?;
+        }
+        #[cfg(feature = "tokenizer")]
+        let fitted;
+        #[cfg(feature = "tokenizer")]
+        let chat_params = if self.fit_max_tokens {
+            let mut chat_params = chat_params.clone();
+            chat_params.fit_max_tokens()?;
+            fitted = chat_params;
+            &fitted
+        } else {
+            chat_params
+        };
+        self.check_budget(Some(&chat_params.model))?;
+        let chat_url = format!("{}/chat/completions", self.api_base);
+
+        let chat_body = serde_json::to_string(chat_params)?;
+
+        let completion: ChatCompletion = self
+            .send(self.http_client.post(chat_url).body(chat_body))
+            .await?;
+        self.record_spend(
+            &completion.model,
+            chat_params.opt.user.as_deref(),
+            completion.usage.as_ref(),
+        );
+        if self.error_on_truncation && completion.is_truncated() {
+            return Err(error::OpenAIError::Truncated);
+        }
+
+        Ok(completion)
+    }
+
+    /// Fails once the budget of the spend tracker, if any, is spent, or when `model` has no price
+    /// to count against it
+    pub(crate) fn check_budget(&self, model: Option<&str>) -> OpenAIResult<()> {
+        let Some(spend_tracker) = &self.spend_tracker else {
+            return Ok(());
+        };
+        spend_tracker.check_budget()?;
+
+        match model {
+            Some(model) => spend_tracker.check_price(model),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn record_spend(&self, model: &str, user: Option<&str>, usage: Option<&Usage>) {
+        if let (Some(spend_tracker), Some(usage)) = (&self.spend_tracker, usage) {
+            spend_tracker.record(model, user, usage);
+        }
+    }
+
+    /// The spend tracker, for streams that record usage after the client is borrowed
+    pub(crate) fn spend_tracker(&self) -> Option<SpendTracker> {
+        self.spend_tracker.clone()
+    }
+
+    /// Checks the budget before `stream` is first polled, so that nothing is sent once it's spent
+    pub(crate) fn budgeted<T, S>(
+        &self,
+        model: Option<&str>,
+        stream: S,
+    ) -> impl Stream<Item = OpenAIResult<T>>
+    where
+        S: Stream<Item = OpenAIResult<T>>,
+    {
+        let checked = self.check_budget(model);
+
+        futures::stream::once(async move { checked.map(|()| stream) }).try_flatten()
+    }
+
+    /// Gives up on `polling` once the poll timeout passes
+    pub(crate) async fn before_poll_timeout<T>(
+        &self,
+        polling: impl Future<Output = OpenAIResult<T>>,
+    ) -> OpenAIResult<T> {
+        tokio::time::timeout(self.poll_timeout, polling)
+            .await
+            .map_err(|_| error::OpenAIError::PollTimeout(self.poll_timeout))?
+    }
+
+    /// Starts a request against one of the endpoints that require the assistants beta header
+    pub(crate) fn beta_request(&self, method: Method, url: String) -> RequestBuilder {
+        self.http_client
+            .request(method, url)
+            .header(OPENAI_BETA.0, OPENAI_BETA.1)
+    }
+
+    /// Sends a request and deserializes the JSON body of a successful response
+    ///
+    /// Unsuccessful responses are mapped onto the matching `OpenAIError` variant
+    pub(crate) async fn send<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+    ) -> OpenAIResult<T> {
+        let resp = request.send().await?;
+
+        if resp.status() == StatusCode::OK {
+            Ok(resp.json::<T>().await?)
+        } else {
+            Err(error::from_response(resp).await)
+        }
+    }
+
+    /// Walks every item of a cursor-paginated list endpoint, fetching the next page only once the
+    /// items of the current one have been consumed
+    ///
+    /// `request` builds the request for the given page params
+    pub(crate) fn paginate<'a, T, F>(
+        &'a self,
+        request: F,
+        params: ListParams,
+    ) -> impl Stream<Item = OpenAIResult<T>> + 'a
+    where
+        T: DeserializeOwned + 'a,
+        F: Fn(&ListParams) -> RequestBuilder + 'a,
+    {
+        self.paginate_with_ids(request, params, None)
+    }
+
+    /// Like `paginate`, for list endpoints whose pages may lack `first_id` and `last_id`, which
+    /// are then taken from the items with `id`
+    pub(crate) fn paginate_with_ids<'a, T, F>(
+        &'a self,
+        request: F,
+        params: ListParams,
+        id: Option<fn(&T) -> &str>,
+    ) -> impl Stream<Item = OpenAIResult<T>> + 'a
+    where
+        T: DeserializeOwned + 'a,
+        F: Fn(&ListParams) -> RequestBuilder + 'a,
+    {
+        futures::stream::try_unfold(Some(params), move |params| {
+            let next = params.map(|params| (request(&params), params));
+
+            async move {
+                let (request, params) = match next {
+                    Some(next) => next,
+                    None => return Ok(None),
+                };
+                let mut page: Page<T> = self.send(request).await?;
+                if let Some(id) = id {
+                    page.fill_cursors(id);
+                }
+                let next_params = page.next_page_params(&params);
+                let items = futures::stream::iter(page.data.into_iter().map(Ok));
+
+                OpenAIResult::Ok(Some((items, next_params)))
+            }
+        })
+        .try_flatten()
+    }
+
+    /// Sends a request to an endpoint that answers with server-sent events
+    ///
+    /// Nothing is sent until the returned stream is first polled
+    pub(crate) fn send_stream(
+        &self,
+        request: RequestBuilder,
+    ) -> impl Stream<Item = OpenAIResult<SseEvent>> {
+        futures::stream::once(async move {
+            let resp = request.send().await?;
+
+            if resp.status() == StatusCode::OK {
+                Ok(sse::events(resp))
+            } else {
+                Err(error::from_response(resp).await)
+            }
+        })
+        .try_flatten()
+    }
 }
 
 #[cfg(test)]
@@ -198,4 +525,102 @@ mod tests {
             client.config.openai_secret_key
         );
     }
+
+    #[test]
+    fn test_invalid_params_rejected_before_sending() {
+        let client = Client::new(String::from("keystring"));
+        let chat_params = ChatParams::builder("gpt-4o-mini")
+            .user_message("Say this is a test")
+            .temperature(5.0)
+            .build()
+            .unwrap();
+
+        let resp = client.create_chat_completion(chat_params);
+        assert!(matches!(resp, Err(error::OpenAIError::InvalidParams(_))));
+    }
+
+    #[test]
+    fn test_validation_can_be_turned_off_for_built_params() {
+        // A compatible server with its own limits
+        let api_base = stand_in::stand_in_server(|request| {
+            assert!(request.contains(r#""temperature":5.0"#));
+            String::from(
+                r#"{"id":"chatcmpl-1","object":"chat.completion","model":"local-model","choices":[{"index":0,"message":{"role":"assistant","content":"A test"},"finish_reason":"stop"}]}"#,
+            )
+        });
+        let client = Client::new(String::from("keystring"))
+            .with_api_base(api_base)
+            .with_validation(false);
+        let chat_params = ChatParams::builder("local-model")
+            .user_message("Say this is a test")
+            .temperature(5.0)
+            .build()
+            .unwrap();
+
+        let completion = client.create_chat_completion(chat_params).unwrap();
+        assert_eq!(completion.choices[0].message.text(), "A test");
+    }
+
+    #[test]
+    fn test_spent_budget_rejected_before_sending() {
+        let spend_tracker = SpendTracker::new().with_budget(0.0);
+        let client = Client::new(String::from("keystring")).with_spend_tracker(spend_tracker);
+        let chat_params = ChatParams::builder("gpt-4o-mini")
+            .user_message("Say this is a test")
+            .build()
+            .unwrap();
+
+        let resp = client.create_chat_completion(chat_params);
+        assert!(matches!(
+            resp,
+            Err(error::OpenAIError::BudgetExceeded { .. })
+        ));
+    }
+
+    #[cfg(feature = "tokenizer")]
+    #[test]
+    fn test_oversized_prompt_rejected_before_sending() {
+        let client = Client::new(String::from("keystring")).with_max_tokens_fitting(true);
+        let completion_params = CompletionParams::builder("davinci")
+            .prompt("hello ".repeat(3000))
+            .build()
+            .unwrap();
+
+        let resp = client.create_completion(completion_params);
+        assert!(matches!(
+            resp,
+            Err(error::OpenAIError::ContextLengthExceeded {
+                context_window: 2049,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_paginate_follows_cursor() {
+        use futures::StreamExt;
+
+        // Serves two pages, picking the second one when the `after` cursor is present
+        let api_base = crate::stand_in::stand_in_server(|request| {
+            let body = if request.contains("after=b") {
+                r#"{"object":"list","data":[{"id":"c"}],"first_id":"c","last_id":"c","has_more":false}"#
+            } else {
+                r#"{"object":"list","data":[{"id":"a"},{"id":"b"}],"first_id":"a","last_id":"b","has_more":true}"#
+            };
+            String::from(body)
+        });
+        let url = format!("{}/items", api_base);
+
+        let client = Client::new(String::from("keystring"));
+        let ids: Vec<String> = client
+            .paginate(
+                |query| client.http_client.get(url.clone()).query(query),
+                ListParams::default(),
+            )
+            .map(|item: OpenAIResult<serde_json::Value>| item.unwrap()["id"].to_string())
+            .collect()
+            .await;
+
+        assert_eq!(ids, vec![r#""a""#, r#""b""#, r#""c""#]);
+    }
 }
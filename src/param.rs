@@ -1,31 +1,154 @@
+use crate::construct::ToolCall;
+use crate::error::{OpenAIError, OpenAIResult};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CompletionParams {
     pub model: String,
-    pub prompt: String,
+    pub prompt: Prompt,
     #[serde(flatten)]
     pub opts: OptParams,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// One or many prompts, given either as text or as token ids
+///
+/// Each prompt of a batch gets its own `n` choices, indexed one prompt after another
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum Prompt {
+    Text(String),
+    TextBatch(Vec<String>),
+    Tokens(Vec<u32>),
+    TokenBatch(Vec<Vec<u32>>),
+}
+
+impl Prompt {
+    /// The number of prompts sent
+    pub fn len(&self) -> usize {
+        match self {
+            Prompt::Text(_) | Prompt::Tokens(_) => 1,
+            Prompt::TextBatch(prompts) => prompts.len(),
+            Prompt::TokenBatch(prompts) => prompts.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl From<String> for Prompt {
+    fn from(value: String) -> Self {
+        Prompt::Text(value)
+    }
+}
+
+impl From<&str> for Prompt {
+    fn from(value: &str) -> Self {
+        Prompt::Text(String::from(value))
+    }
+}
+
+impl From<Vec<String>> for Prompt {
+    fn from(value: Vec<String>) -> Self {
+        Prompt::TextBatch(value)
+    }
+}
+
+impl From<Vec<&str>> for Prompt {
+    fn from(value: Vec<&str>) -> Self {
+        Prompt::TextBatch(value.into_iter().map(String::from).collect())
+    }
+}
+
+impl From<Vec<u32>> for Prompt {
+    fn from(value: Vec<u32>) -> Self {
+        Prompt::Tokens(value)
+    }
+}
+
+impl From<Vec<Vec<u32>>> for Prompt {
+    fn from(value: Vec<Vec<u32>>) -> Self {
+        Prompt::TokenBatch(value)
+    }
+}
+
+/// Up to 4 sequences where the API stops generating further tokens
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum Stop {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+impl Stop {
+    pub fn sequences(&self) -> &[String] {
+        match self {
+            Stop::Single(stop) => std::slice::from_ref(stop),
+            Stop::Multiple(stops) => stops,
+        }
+    }
+}
+
+impl<S: Into<String>> FromIterator<S> for Stop {
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        let mut stops: Vec<String> = iter.into_iter().map(Into::into).collect();
+        if stops.len() == 1 {
+            Stop::Single(stops.remove(0))
+        } else {
+            Stop::Multiple(stops)
+        }
+    }
+}
+
+/// Asks a streamed response for a final chunk carrying the usage of the whole request
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct StreamOptions {
+    pub include_usage: bool,
+}
+
+/// Every field left as `None` is omitted from the request, so that the server default applies
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct OptParams {
-    pub max_tokens: usize,
-    pub temperature: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub suffix: Option<String>,
-    pub top_p: f32,
-    pub n: usize,
-    pub stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub logprobs: Option<usize>,
-    pub echo: bool,
-    pub stop: Option<String>,
-    pub presence_penalty: f32,
-    pub frequency_penalty: f32,
-    pub best_of: usize,
-    pub user: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub echo: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Stop>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub best_of: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Maps token ids to a bias between -100 and 100 added to their logits
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logit_bias: Option<HashMap<u32, i32>>,
+    /// Makes sampling deterministic on a best-effort basis
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i64>,
+    /// Only allowed with `stream`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<StreamOptions>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ChatParams {
     pub model: String,
     pub messages: Vec<Message>,
@@ -33,30 +156,365 @@ pub struct ChatParams {
     pub opt: OptChatParams,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-#[serde(rename_all = "lowercase")]
+/// A chat message, as sent to the API and as returned in `ChatCompletion` choices
+///
+/// `content` is only absent on assistant messages that carry `tool_calls`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Message {
     pub role: Role,
-    pub content: String,
+    pub content: Option<Content>,
+    /// Tells apart participants that share a role
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ToolCall>>,
+    /// The tool call a `Role::Tool` message answers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+    /// Set instead of `content` when the model declines to answer with a `response_format` schema
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub refusal: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+impl Message {
+    fn new(role: Role, content: Content) -> Self {
+        Message {
+            role,
+            content: Some(content),
+            name: None,
+            tool_calls: None,
+            tool_call_id: None,
+            refusal: None,
+        }
+    }
+
+    /// Instructions for models that follow developer messages in place of system messages
+    pub fn developer(content: String) -> Self {
+        Message::new(Role::Developer, Content::Text(content))
+    }
+
+    pub fn system(content: String) -> Self {
+        Message::new(Role::System, Content::Text(content))
+    }
+
+    pub fn user(content: String) -> Self {
+        Message::new(Role::User, Content::Text(content))
+    }
+
+    /// A user message mixing text with images, audio or files
+    pub fn user_parts(parts: Vec<ContentPart>) -> Self {
+        Message::new(Role::User, Content::Parts(parts))
+    }
+
+    pub fn assistant(content: String) -> Self {
+        Message::new(Role::Assistant, Content::Text(content))
+    }
+
+    /// The result of running the tool call with the given id
+    pub fn tool(tool_call_id: String, content: String) -> Self {
+        Message {
+            tool_call_id: Some(tool_call_id),
+            ..Message::new(Role::Tool, Content::Text(content))
+        }
+    }
+
+    pub fn with_name(mut self, name: String) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    /// The text of the message, leaving out any non-text parts
+    pub fn text(&self) -> String {
+        match &self.content {
+            Some(Content::Text(text)) => text.clone(),
+            Some(Content::Parts(parts)) => parts
+                .iter()
+                .filter_map(|part| match part {
+                    ContentPart::Text { text } => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect(),
+            None => String::new(),
+        }
+    }
+
+    /// The tool calls requested by an assistant message
+    pub fn tool_calls(&self) -> &[ToolCall] {
+        self.tool_calls.as_deref().unwrap_or_default()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
 pub enum Role {
+    Developer,
     User,
     System,
+    Assistant,
+    Tool,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+impl Role {
+    /// The role as it is sent to the API
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Developer => "developer",
+            Role::User => "user",
+            Role::System => "system",
+            Role::Assistant => "assistant",
+            Role::Tool => "tool",
+        }
+    }
+}
+
+/// Message content, serialized as a plain string or as an array of typed parts
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum Content {
+    Text(String),
+    Parts(Vec<ContentPart>),
+}
+
+impl From<String> for Content {
+    fn from(value: String) -> Self {
+        Content::Text(value)
+    }
+}
+
+/// Only user messages accept images, audio and files, and only assistant messages accept refusals
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentPart {
+    Text { text: String },
+    ImageUrl { image_url: ImageUrl },
+    InputAudio { input_audio: InputAudio },
+    File { file: FileInput },
+    Refusal { refusal: String },
+}
+
+impl ContentPart {
+    pub fn text(text: String) -> Self {
+        ContentPart::Text { text }
+    }
+
+    /// An image given by URL, either remote or a `data:` URL
+    pub fn image_url(url: String) -> Self {
+        ContentPart::ImageUrl {
+            image_url: ImageUrl { url, detail: None },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ImageUrl {
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<ImageDetail>,
+}
+
+/// How closely the model looks at an image. `Low` costs a fixed amount of tokens
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageDetail {
+    Auto,
+    Low,
+    High,
+}
+
+/// Base64 encoded audio
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InputAudio {
+    pub data: String,
+    pub format: AudioFormat,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioFormat {
+    Wav,
+    Mp3,
+}
+
+/// A file given either by the id of an uploaded file or inline as base64 `file_data`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FileInput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_data: Option<String>,
+}
+
+/// Every field left as `None` is omitted from the request, so that the server default applies
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct OptChatParams {
-    pub temperature: f32,
-    pub top_p: f32,
-    pub n: usize,
-    pub stream: bool,
-    pub stop: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Stop>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub max_tokens: Option<u64>,
-    pub presence_penalty: f32,
-    pub frequency_penalty: f32,
-    pub user: String,
+    /// Bounds the completion including its reasoning tokens. Reasoning models only accept this
+    /// in place of `max_tokens`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_completion_tokens: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logit_bias: Option<HashMap<u32, i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i64>,
+    /// Returns the logprob of each content token in `ChatChoice::logprobs`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logprobs: Option<bool>,
+    /// How many of the most likely tokens to return at each position, up to 20. Requires `logprobs`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_logprobs: Option<u8>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub tools: Vec<Tool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<ToolChoice>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parallel_tool_calls: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ResponseFormat>,
+}
+
+/// The format the model must answer in
+///
+/// `JsonObject` only guarantees valid JSON, while `JsonSchema` holds the reply to a schema
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseFormat {
+    Text,
+    JsonObject,
+    JsonSchema { json_schema: JsonSchemaFormat },
+}
+
+impl ResponseFormat {
+    /// A strict JSON Schema format, see `JsonSchemaFormat::strict`
+    pub fn json_schema(name: String, schema: serde_json::Value) -> Self {
+        ResponseFormat::JsonSchema {
+            json_schema: JsonSchemaFormat {
+                name,
+                description: None,
+                schema: Some(schema),
+                strict: Some(true),
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JsonSchemaFormat {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema: Option<serde_json::Value>,
+    /// Strict schemas must list every property as required and disallow additional properties
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strict: Option<bool>,
+}
+
+/// A tool the model may call. Only functions are supported by chat completions
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Tool {
+    Function { function: FunctionDefinition },
+}
+
+impl Tool {
+    /// A function tool whose arguments are described by the `parameters` JSON Schema
+    pub fn function(name: String, description: String, parameters: serde_json::Value) -> Self {
+        Tool::Function {
+            function: FunctionDefinition {
+                name,
+                description: Some(description),
+                parameters: Some(parameters),
+                strict: None,
+            },
+        }
+    }
+}
+
+/// Controls whether the model calls tools, serialized as either a mode or a named function
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum ToolChoice {
+    Mode(ToolChoiceMode),
+    Named(NamedToolChoice),
+}
+
+impl ToolChoice {
+    /// Forces the model to call the named function
+    pub fn function(name: String) -> Self {
+        ToolChoice::Named(NamedToolChoice::Function {
+            function: FunctionName { name },
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolChoiceMode {
+    None,
+    Auto,
+    Required,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NamedToolChoice {
+    Function { function: FunctionName },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FunctionName {
+    pub name: String,
+}
+
+/// A function the model may call, described with a JSON Schema for its parameters
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FunctionDefinition {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strict: Option<bool>,
+}
+
+/// Query params shared by the cursor-paginated list endpoints
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct ListParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<ListOrder>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ListOrder {
+    Asc,
+    Desc,
 }
 
 /// Only use text-davinci-edit-001 or code-davinci-edit-001 models with this endpoint
@@ -70,41 +528,529 @@ pub struct EditParams {
     pub opts: OptEditParams,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct OptEditParams {
-    #[serde(rename = "n")]
-    pub num_edits: usize,
-    pub temperature: f32,
-    pub top_p: usize,
+    #[serde(rename = "n", skip_serializing_if = "Option::is_none")]
+    pub num_edits: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+}
+
+impl CompletionParams {
+    /// Checks the params against the documented API constraints
+    ///
+    /// # Errors
+    ///
+    /// Returns `OpenAIError::InvalidParams` listing every violated constraint
+    pub fn validate(&self) -> OpenAIResult<()> {
+        let mut violations = Vec::new();
+        let opts = &self.opts;
+
+        check_range(&mut violations, "temperature", opts.temperature, 0.0, 2.0);
+        check_range(&mut violations, "top_p", opts.top_p, 0.0, 1.0);
+        check_range(
+            &mut violations,
+            "presence_penalty",
+            opts.presence_penalty,
+            -2.0,
+            2.0,
+        );
+        check_range(
+            &mut violations,
+            "frequency_penalty",
+            opts.frequency_penalty,
+            -2.0,
+            2.0,
+        );
+        check_at_least_one(&mut violations, "max_tokens", opts.max_tokens);
+        check_at_least_one(&mut violations, "n", opts.n);
+        if let Some(logprobs) = opts.logprobs.filter(|&logprobs| logprobs > 5) {
+            violations.push(format!("logprobs must be at most 5, got {}", logprobs));
+        }
+        if let (Some(n), Some(best_of)) = (opts.n, opts.best_of) {
+            if n > best_of {
+                violations.push(format!(
+                    "n must not be greater than best_of, got n = {} and best_of = {}",
+                    n, best_of
+                ));
+            }
+        }
+        if self.prompt.is_empty() {
+            violations.push(String::from("prompt must not be an empty batch"));
+        }
+        check_stop(&mut violations, &opts.stop);
+        check_logit_bias(&mut violations, &opts.logit_bias);
+        if opts.stream_options.is_some() && opts.stream != Some(true) {
+            violations.push(String::from("stream_options requires stream to be set"));
+        }
+
+        into_result(violations)
+    }
+}
+
+impl ChatParams {
+    /// Checks the params against the documented API constraints
+    ///
+    /// # Errors
+    ///
+    /// Returns `OpenAIError::InvalidParams` listing every violated constraint
+    pub fn validate(&self) -> OpenAIResult<()> {
+        let mut violations = Vec::new();
+        let opt = &self.opt;
+
+        if self.messages.is_empty() {
+            violations.push(String::from("messages must not be empty"));
+        }
+        check_range(&mut violations, "temperature", opt.temperature, 0.0, 2.0);
+        check_range(&mut violations, "top_p", opt.top_p, 0.0, 1.0);
+        check_range(
+            &mut violations,
+            "presence_penalty",
+            opt.presence_penalty,
+            -2.0,
+            2.0,
+        );
+        check_range(
+            &mut violations,
+            "frequency_penalty",
+            opt.frequency_penalty,
+            -2.0,
+            2.0,
+        );
+        check_at_least_one(&mut violations, "max_tokens", opt.max_tokens);
+        check_at_least_one(
+            &mut violations,
+            "max_completion_tokens",
+            opt.max_completion_tokens,
+        );
+        check_at_least_one(&mut violations, "n", opt.n);
+        check_stop(&mut violations, &opt.stop);
+        check_logit_bias(&mut violations, &opt.logit_bias);
+        check_range(&mut violations, "top_logprobs", opt.top_logprobs, 0, 20);
+        if opt.top_logprobs.is_some() && opt.logprobs != Some(true) {
+            violations.push(String::from("top_logprobs requires logprobs to be set"));
+        }
+
+        into_result(violations)
+    }
+}
+
+impl EditParams {
+    /// Checks the params against the documented API constraints
+    ///
+    /// # Errors
+    ///
+    /// Returns `OpenAIError::InvalidParams` listing every violated constraint
+    pub fn validate(&self) -> OpenAIResult<()> {
+        let mut violations = Vec::new();
+        let opts = &self.opts;
+
+        check_range(&mut violations, "temperature", opts.temperature, 0.0, 2.0);
+        check_range(&mut violations, "top_p", opts.top_p, 0.0, 1.0);
+        check_at_least_one(&mut violations, "n", opts.num_edits);
+
+        into_result(violations)
+    }
+}
+
+fn check_range<T: PartialOrd + std::fmt::Display>(
+    violations: &mut Vec<String>,
+    name: &str,
+    value: Option<T>,
+    min: T,
+    max: T,
+) {
+    if let Some(value) = value.filter(|value| *value < min || *value > max) {
+        violations.push(format!(
+            "{} must be between {} and {}, got {}",
+            name, min, max, value
+        ));
+    }
+}
+
+fn check_at_least_one<T: PartialOrd + From<u8>>(
+    violations: &mut Vec<String>,
+    name: &str,
+    value: Option<T>,
+) {
+    if value.is_some_and(|value| value < T::from(1)) {
+        violations.push(format!("{} must be at least 1", name));
+    }
+}
+
+fn check_stop(violations: &mut Vec<String>, stop: &Option<Stop>) {
+    let count = stop.as_ref().map_or(0, |stop| stop.sequences().len());
+    if count > 4 {
+        violations.push(format!("stop must have at most 4 sequences, got {}", count));
+    }
+}
+
+fn check_logit_bias(violations: &mut Vec<String>, logit_bias: &Option<HashMap<u32, i32>>) {
+    let mut out_of_range: Vec<_> = logit_bias
+        .iter()
+        .flatten()
+        .filter(|(_, bias)| !(-100..=100).contains(*bias))
+        .map(|(token, _)| *token)
+        .collect();
+    out_of_range.sort_unstable();
+    for token in out_of_range {
+        violations.push(format!(
+            "logit_bias for token {} must be between -100 and 100",
+            token
+        ));
+    }
+}
+
+fn into_result(violations: Vec<String>) -> OpenAIResult<()> {
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(OpenAIError::InvalidParams(violations))
+    }
+}
+
+impl CompletionParams {
+    /// Starts building completion params for the given model
+    pub fn builder(model: impl Into<String>) -> CompletionParamsBuilder {
+        CompletionParamsBuilder {
+            model: model.into(),
+            prompt: None,
+            opts: OptParams::default(),
+        }
+    }
 }
 
-impl Default for OptEditParams {
-    fn default() -> Self {
-        Self {
-            num_edits: 1,
-            temperature: 1.0,
-            top_p: 1,
+/// Builds `CompletionParams`, created with `CompletionParams::builder`
+#[derive(Debug)]
+pub struct CompletionParamsBuilder {
+    model: String,
+    prompt: Option<Prompt>,
+    opts: OptParams,
+}
+
+impl CompletionParamsBuilder {
+    /// Sets the prompt, or a batch of prompts
+    pub fn prompt(mut self, prompt: impl Into<Prompt>) -> Self {
+        self.prompt = Some(prompt.into());
+        self
+    }
+
+    pub fn max_tokens(mut self, max_tokens: usize) -> Self {
+        self.opts.max_tokens = Some(max_tokens);
+        self
+    }
+
+    pub fn temperature(mut self, temperature: f32) -> Self {
+        self.opts.temperature = Some(temperature);
+        self
+    }
+
+    pub fn suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.opts.suffix = Some(suffix.into());
+        self
+    }
+
+    pub fn top_p(mut self, top_p: f32) -> Self {
+        self.opts.top_p = Some(top_p);
+        self
+    }
+
+    pub fn n(mut self, n: usize) -> Self {
+        self.opts.n = Some(n);
+        self
+    }
+
+    pub fn logprobs(mut self, logprobs: usize) -> Self {
+        self.opts.logprobs = Some(logprobs);
+        self
+    }
+
+    pub fn echo(mut self, echo: bool) -> Self {
+        self.opts.echo = Some(echo);
+        self
+    }
+
+    pub fn stop<S: Into<String>>(mut self, stop: impl IntoIterator<Item = S>) -> Self {
+        self.opts.stop = Some(stop.into_iter().collect());
+        self
+    }
+
+    pub fn presence_penalty(mut self, presence_penalty: f32) -> Self {
+        self.opts.presence_penalty = Some(presence_penalty);
+        self
+    }
+
+    pub fn frequency_penalty(mut self, frequency_penalty: f32) -> Self {
+        self.opts.frequency_penalty = Some(frequency_penalty);
+        self
+    }
+
+    pub fn best_of(mut self, best_of: usize) -> Self {
+        self.opts.best_of = Some(best_of);
+        self
+    }
+
+    pub fn user(mut self, user: impl Into<String>) -> Self {
+        self.opts.user = Some(user.into());
+        self
+    }
+
+    /// Adds a bias between -100 and 100 to the logit of a token
+    pub fn logit_bias(mut self, token: u32, bias: i32) -> Self {
+        self.opts
+            .logit_bias
+            .get_or_insert_with(HashMap::new)
+            .insert(token, bias);
+        self
+    }
+
+    pub fn seed(mut self, seed: i64) -> Self {
+        self.opts.seed = Some(seed);
+        self
+    }
+
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _no prompt was given_
+    ///
+    /// The params are checked against the API constraints once sent, see `Client::with_validation`
+    pub fn build(self) -> OpenAIResult<CompletionParams> {
+        let prompt = self
+            .prompt
+            .ok_or_else(|| OpenAIError::BuilderError(String::from("a prompt is required")))?;
+        let completion_params = CompletionParams {
+            model: self.model,
+            prompt,
+            opts: self.opts,
+        };
+        Ok(completion_params)
+    }
+}
+
+impl ChatParams {
+    /// Starts building chat params for the given model
+    pub fn builder(model: impl Into<String>) -> ChatParamsBuilder {
+        ChatParamsBuilder {
+            model: model.into(),
+            messages: Vec::new(),
+            opt: OptChatParams::default(),
         }
     }
 }
 
-impl Default for OptParams {
-    fn default() -> Self {
-        Self {
-            max_tokens: 16,
-            temperature: 1.0,
-            suffix: None,
-            top_p: 1.0,
-            n: 1,
-            stream: false,
-            logprobs: None,
-            echo: false,
-            stop: None,
-            presence_penalty: 0.0,
-            frequency_penalty: 0.0,
-            best_of: 1,
-            user: String::new(),
+/// Builds `ChatParams`, created with `ChatParams::builder`
+#[derive(Debug)]
+pub struct ChatParamsBuilder {
+    model: String,
+    messages: Vec<Message>,
+    opt: OptChatParams,
+}
+
+impl ChatParamsBuilder {
+    pub fn message(mut self, message: Message) -> Self {
+        self.messages.push(message);
+        self
+    }
+
+    pub fn messages(mut self, messages: impl IntoIterator<Item = Message>) -> Self {
+        self.messages.extend(messages);
+        self
+    }
+
+    pub fn developer_message(self, content: impl Into<String>) -> Self {
+        self.message(Message::developer(content.into()))
+    }
+
+    pub fn system_message(self, content: impl Into<String>) -> Self {
+        self.message(Message::system(content.into()))
+    }
+
+    pub fn user_message(self, content: impl Into<String>) -> Self {
+        self.message(Message::user(content.into()))
+    }
+
+    pub fn assistant_message(self, content: impl Into<String>) -> Self {
+        self.message(Message::assistant(content.into()))
+    }
+
+    pub fn temperature(mut self, temperature: f32) -> Self {
+        self.opt.temperature = Some(temperature);
+        self
+    }
+
+    pub fn top_p(mut self, top_p: f32) -> Self {
+        self.opt.top_p = Some(top_p);
+        self
+    }
+
+    pub fn n(mut self, n: usize) -> Self {
+        self.opt.n = Some(n);
+        self
+    }
+
+    pub fn stop<S: Into<String>>(mut self, stop: impl IntoIterator<Item = S>) -> Self {
+        self.opt.stop = Some(stop.into_iter().collect());
+        self
+    }
+
+    pub fn max_tokens(mut self, max_tokens: u64) -> Self {
+        self.opt.max_tokens = Some(max_tokens);
+        self
+    }
+
+    pub fn max_completion_tokens(mut self, max_completion_tokens: u64) -> Self {
+        self.opt.max_completion_tokens = Some(max_completion_tokens);
+        self
+    }
+
+    pub fn presence_penalty(mut self, presence_penalty: f32) -> Self {
+        self.opt.presence_penalty = Some(presence_penalty);
+        self
+    }
+
+    pub fn frequency_penalty(mut self, frequency_penalty: f32) -> Self {
+        self.opt.frequency_penalty = Some(frequency_penalty);
+        self
+    }
+
+    /// Sets the end-user identifier. Use `user_message` to add a user message
+    pub fn user(mut self, user: impl Into<String>) -> Self {
+        self.opt.user = Some(user.into());
+        self
+    }
+
+    pub fn tool(mut self, tool: Tool) -> Self {
+        self.opt.tools.push(tool);
+        self
+    }
+
+    pub fn tool_choice(mut self, tool_choice: ToolChoice) -> Self {
+        self.opt.tool_choice = Some(tool_choice);
+        self
+    }
+
+    pub fn parallel_tool_calls(mut self, parallel_tool_calls: bool) -> Self {
+        self.opt.parallel_tool_calls = Some(parallel_tool_calls);
+        self
+    }
+
+    pub fn response_format(mut self, response_format: ResponseFormat) -> Self {
+        self.opt.response_format = Some(response_format);
+        self
+    }
+
+    /// Adds a bias between -100 and 100 to the logit of a token
+    pub fn logit_bias(mut self, token: u32, bias: i32) -> Self {
+        self.opt
+            .logit_bias
+            .get_or_insert_with(HashMap::new)
+            .insert(token, bias);
+        self
+    }
+
+    pub fn seed(mut self, seed: i64) -> Self {
+        self.opt.seed = Some(seed);
+        self
+    }
+
+    /// Requests the logprobs of the sampled tokens, along with the `top_logprobs` most likely
+    /// tokens at each position
+    pub fn logprobs(mut self, top_logprobs: u8) -> Self {
+        self.opt.logprobs = Some(true);
+        self.opt.top_logprobs = Some(top_logprobs);
+        self
+    }
+
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _no message was given_
+    ///
+    /// The params are checked against the API constraints once sent, see `Client::with_validation`
+    pub fn build(self) -> OpenAIResult<ChatParams> {
+        if self.messages.is_empty() {
+            return Err(OpenAIError::BuilderError(String::from(
+                "at least one message is required",
+            )));
         }
+        let chat_params = ChatParams {
+            model: self.model,
+            messages: self.messages,
+            opt: self.opt,
+        };
+        Ok(chat_params)
+    }
+}
+
+impl EditParams {
+    /// Starts building edit params for the given model
+    pub fn builder(model: impl Into<String>) -> EditParamsBuilder {
+        EditParamsBuilder {
+            model: model.into(),
+            input: String::new(),
+            instruction: None,
+            opts: OptEditParams::default(),
+        }
+    }
+}
+
+/// Builds `EditParams`, created with `EditParams::builder`
+#[derive(Debug)]
+pub struct EditParamsBuilder {
+    model: String,
+    input: String,
+    instruction: Option<String>,
+    opts: OptEditParams,
+}
+
+impl EditParamsBuilder {
+    /// The text to edit, empty by default
+    pub fn input(mut self, input: impl Into<String>) -> Self {
+        self.input = input.into();
+        self
+    }
+
+    pub fn instruction(mut self, instruction: impl Into<String>) -> Self {
+        self.instruction = Some(instruction.into());
+        self
+    }
+
+    pub fn num_edits(mut self, num_edits: usize) -> Self {
+        self.opts.num_edits = Some(num_edits);
+        self
+    }
+
+    pub fn temperature(mut self, temperature: f32) -> Self {
+        self.opts.temperature = Some(temperature);
+        self
+    }
+
+    pub fn top_p(mut self, top_p: f32) -> Self {
+        self.opts.top_p = Some(top_p);
+        self
+    }
+
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _no instruction was given_
+    ///
+    /// The params can be checked against the API constraints with `EditParams::validate`
+    pub fn build(self) -> OpenAIResult<EditParams> {
+        let instruction = self
+            .instruction
+            .ok_or_else(|| OpenAIError::BuilderError(String::from("an instruction is required")))?;
+
+        let edit_params = EditParams {
+            model: self.model,
+            input: self.input,
+            instruction,
+            opts: self.opts,
+        };
+        Ok(edit_params)
     }
 }
 
@@ -115,19 +1061,19 @@ mod tests {
     #[test]
     fn test_completion_serialization() {
         let opt_params = OptParams {
-            max_tokens: 7,
-            temperature: 0.0,
+            max_tokens: Some(7),
+            temperature: Some(0.0),
             ..Default::default()
         };
         let completion_params: CompletionParams = CompletionParams {
             model: String::from("text-davinci-003"),
-            prompt: String::from("Say this is a test"),
+            prompt: Prompt::from("Say this is a test"),
             opts: opt_params,
         };
 
         let completion_serialized = serde_json::to_string(&completion_params).unwrap();
 
-        let param_json = r#"{"model":"text-davinci-003","prompt":"Say this is a test","max_tokens":7,"temperature":0.0,"suffix":null,"top_p":1.0,"n":1,"stream":false,"logprobs":null,"echo":false,"stop":null,"presence_penalty":0.0,"frequency_penalty":0.0,"best_of":1,"user":""}"#;
+        let param_json = r#"{"model":"text-davinci-003","prompt":"Say this is a test","max_tokens":7,"temperature":0.0}"#;
 
         // println!("{}", params);
         assert_eq!(completion_serialized, param_json);
@@ -139,11 +1085,127 @@ mod tests {
 
         let opt_serialized = serde_json::to_string(&opt_params).unwrap();
 
-        let opt_json = r#"{"max_tokens":16,"temperature":1.0,"suffix":null,"top_p":1.0,"n":1,"stream":false,"logprobs":null,"echo":false,"stop":null,"presence_penalty":0.0,"frequency_penalty":0.0,"best_of":1,"user":""}"#;
+        let opt_json = r#"{}"#;
 
         assert_eq!(opt_serialized, opt_json);
     }
 
+    #[test]
+    fn test_chat_tools_serialization() {
+        let chat_params = ChatParams {
+            model: String::from("gpt-4o-mini"),
+            messages: vec![Message::user(String::from("Weather in Paris?"))],
+            opt: OptChatParams {
+                tools: vec![Tool::function(
+                    String::from("get_weather"),
+                    String::from("Current weather for a city"),
+                    serde_json::json!({"type": "object", "properties": {"city": {"type": "string"}}}),
+                )],
+                tool_choice: Some(ToolChoice::Mode(ToolChoiceMode::Auto)),
+                ..Default::default()
+            },
+        };
+
+        let chat_serialized = serde_json::to_value(&chat_params).unwrap();
+
+        assert_eq!(
+            chat_serialized["messages"],
+            serde_json::json!([{"role": "user", "content": "Weather in Paris?"}])
+        );
+        assert_eq!(
+            chat_serialized["tools"],
+            serde_json::json!([{
+                "type": "function",
+                "function": {
+                    "name": "get_weather",
+                    "description": "Current weather for a city",
+                    "parameters": {"type": "object", "properties": {"city": {"type": "string"}}}
+                }
+            }])
+        );
+        assert_eq!(chat_serialized["tool_choice"], "auto");
+        assert_eq!(
+            serde_json::to_string(&ToolChoice::function(String::from("get_weather"))).unwrap(),
+            r#"{"type":"function","function":{"name":"get_weather"}}"#
+        );
+    }
+
+    #[test]
+    fn test_tool_message_serialization() {
+        let tool_message = Message::tool(String::from("call_abc"), String::from("22C"));
+
+        assert_eq!(
+            serde_json::to_string(&tool_message).unwrap(),
+            r#"{"role":"tool","content":"22C","tool_call_id":"call_abc"}"#
+        );
+    }
+
+    #[test]
+    fn test_multi_part_message_serialization() {
+        let message = Message::user_parts(vec![
+            ContentPart::text(String::from("What is in this image?")),
+            ContentPart::ImageUrl {
+                image_url: ImageUrl {
+                    url: String::from("https://example.com/cat.png"),
+                    detail: Some(ImageDetail::Low),
+                },
+            },
+        ])
+        .with_name(String::from("alice"));
+
+        assert_eq!(
+            serde_json::to_value(&message).unwrap(),
+            serde_json::json!({
+                "role": "user",
+                "content": [
+                    {"type": "text", "text": "What is in this image?"},
+                    {"type": "image_url", "image_url": {"url": "https://example.com/cat.png", "detail": "low"}}
+                ],
+                "name": "alice"
+            })
+        );
+        assert_eq!(message.text(), "What is in this image?");
+
+        let refused: Message = serde_json::from_str(
+            r#"{"role":"assistant","content":[{"type":"refusal","refusal":"I can't help with that."}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            refused.content,
+            Some(Content::Parts(vec![ContentPart::Refusal {
+                refusal: String::from("I can't help with that.")
+            }]))
+        );
+        assert_eq!(refused.text(), "");
+
+        let developer: Message =
+            serde_json::from_str(r#"{"role":"developer","content":"Answer in French"}"#).unwrap();
+        assert_eq!(
+            developer,
+            Message::developer(String::from("Answer in French"))
+        );
+    }
+
+    #[test]
+    fn test_response_format_serialization() {
+        assert_eq!(
+            serde_json::to_string(&ResponseFormat::JsonObject).unwrap(),
+            r#"{"type":"json_object"}"#
+        );
+
+        let format = ResponseFormat::json_schema(
+            String::from("answer"),
+            serde_json::json!({"type": "object"}),
+        );
+        assert_eq!(
+            serde_json::to_value(&format).unwrap(),
+            serde_json::json!({
+                "type": "json_schema",
+                "json_schema": {"name": "answer", "schema": {"type": "object"}, "strict": true}
+            })
+        );
+    }
+
     #[test]
     fn test_edit_params() {
         let opt_params: OptEditParams = OptEditParams::default();
@@ -156,8 +1218,191 @@ mod tests {
 
         let params_serialized = serde_json::to_string(&edit_params).unwrap();
 
-        let params_json = r#"{"model":"text-davinci-edit-001","input":"What day of the wek is it?","instruction":"Fix the spelling mistakes","n":1,"temperature":1.0,"top_p":1}"#;
+        let params_json = r#"{"model":"text-davinci-edit-001","input":"What day of the wek is it?","instruction":"Fix the spelling mistakes"}"#;
 
         assert_eq!(params_serialized, params_json);
     }
+
+    #[test]
+    fn test_completion_builder() {
+        let completion_params = CompletionParams::builder("gpt-3.5-turbo-instruct")
+            .prompt("Say this is a test")
+            .max_tokens(64)
+            .temperature(0.2)
+            .stop(["\n"])
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            serde_json::to_string(&completion_params).unwrap(),
+            r#"{"model":"gpt-3.5-turbo-instruct","prompt":"Say this is a test","max_tokens":64,"temperature":0.2,"stop":"\n"}"#
+        );
+
+        let missing_prompt = CompletionParams::builder("gpt-3.5-turbo-instruct").build();
+        assert!(matches!(missing_prompt, Err(OpenAIError::BuilderError(_))));
+    }
+
+    #[test]
+    fn test_chat_builder() {
+        let chat_params = ChatParams::builder("gpt-4o-mini")
+            .system_message("You are terse")
+            .user_message("Say this is a test")
+            .max_tokens(16)
+            .user("user-1234")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            chat_params.messages,
+            vec![
+                Message::system(String::from("You are terse")),
+                Message::user(String::from("Say this is a test")),
+            ]
+        );
+        assert_eq!(chat_params.opt.max_tokens, Some(16));
+        assert_eq!(chat_params.opt.user.as_deref(), Some("user-1234"));
+
+        let no_messages = ChatParams::builder("gpt-4o-mini").build();
+        assert!(matches!(no_messages, Err(OpenAIError::BuilderError(_))));
+    }
+
+    #[test]
+    fn test_edit_builder() {
+        let edit_params = EditParams::builder("text-davinci-edit-001")
+            .input("What day of the wek is it?")
+            .instruction("Fix the spelling mistakes")
+            .num_edits(2)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            serde_json::to_string(&edit_params).unwrap(),
+            r#"{"model":"text-davinci-edit-001","input":"What day of the wek is it?","instruction":"Fix the spelling mistakes","n":2}"#
+        );
+    }
+
+    #[test]
+    fn test_completion_validation() {
+        let completion_params = CompletionParams {
+            model: String::from("gpt-3.5-turbo-instruct"),
+            prompt: Prompt::from("Say this is a test"),
+            opts: OptParams {
+                temperature: Some(5.0),
+                top_p: Some(1.5),
+                logprobs: Some(6),
+                n: Some(3),
+                best_of: Some(2),
+                ..Default::default()
+            },
+        };
+
+        let Err(OpenAIError::InvalidParams(violations)) = completion_params.validate() else {
+            panic!("expected invalid params");
+        };
+        assert_eq!(
+            violations,
+            vec![
+                "temperature must be between 0 and 2, got 5",
+                "top_p must be between 0 and 1, got 1.5",
+                "logprobs must be at most 5, got 6",
+                "n must not be greater than best_of, got n = 3 and best_of = 2",
+            ]
+        );
+
+        let builder_result = CompletionParams::builder("gpt-3.5-turbo-instruct")
+            .prompt("Say this is a test")
+            .temperature(0.7)
+            .build();
+        assert!(builder_result.is_ok());
+    }
+
+    #[test]
+    fn test_chat_validation() {
+        let chat_params = ChatParams {
+            model: String::from("gpt-4o-mini"),
+            messages: Vec::new(),
+            opt: OptChatParams {
+                frequency_penalty: Some(-3.0),
+                ..Default::default()
+            },
+        };
+
+        let Err(OpenAIError::InvalidParams(violations)) = chat_params.validate() else {
+            panic!("expected invalid params");
+        };
+        assert_eq!(
+            violations,
+            vec![
+                "messages must not be empty",
+                "frequency_penalty must be between -2 and 2, got -3",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_edit_validation() {
+        let edit_params = EditParams::builder("text-davinci-edit-001")
+            .input("What day of the wek is it?")
+            .instruction("Fix the spelling mistakes")
+            .top_p(0.5);
+        assert_eq!(edit_params.opts.top_p, Some(0.5));
+        assert!(edit_params.build().unwrap().validate().is_ok());
+
+        let out_of_range = EditParams::builder("text-davinci-edit-001")
+            .input("What day of the wek is it?")
+            .instruction("Fix the spelling mistakes")
+            .top_p(1.5)
+            .build()
+            .unwrap();
+        let Err(OpenAIError::InvalidParams(violations)) = out_of_range.validate() else {
+            panic!("expected invalid params");
+        };
+        assert_eq!(violations, vec!["top_p must be between 0 and 1, got 1.5"]);
+    }
+
+    #[test]
+    fn test_batched_prompt_serialization() {
+        let completion_params = CompletionParams::builder("gpt-3.5-turbo-instruct")
+            .prompt(vec!["Say this is a test", "Say this is another test"])
+            .stop(["\n", "."])
+            .logit_bias(50256, -100)
+            .seed(42)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&completion_params).unwrap(),
+            serde_json::json!({
+                "model": "gpt-3.5-turbo-instruct",
+                "prompt": ["Say this is a test", "Say this is another test"],
+                "stop": ["\n", "."],
+                "logit_bias": {"50256": -100},
+                "seed": 42
+            })
+        );
+
+        let tokens: Prompt = serde_json::from_str("[[1, 2], [3]]").unwrap();
+        assert_eq!(tokens, Prompt::TokenBatch(vec![vec![1, 2], vec![3]]));
+    }
+
+    #[test]
+    fn test_stop_and_logit_bias_validation() {
+        let result = CompletionParams::builder("gpt-3.5-turbo-instruct")
+            .prompt("Say this is a test")
+            .stop(["a", "b", "c", "d", "e"])
+            .logit_bias(50256, 101)
+            .build()
+            .unwrap();
+
+        let Err(OpenAIError::InvalidParams(violations)) = result.validate() else {
+            panic!("expected invalid params");
+        };
+        assert_eq!(
+            violations,
+            vec![
+                "stop must have at most 4 sequences, got 5",
+                "logit_bias for token 50256 must be between -100 and 100",
+            ]
+        );
+    }
 }
@@ -0,0 +1,482 @@
+use crate::construct::{ChatCompletion, Completion, Usage};
+use crate::embeddings::Embeddings;
+use crate::error::{OpenAIError, OpenAIResult};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Built-in prices of the standard tier, in US dollars per 1M tokens
+const PRICES: &[(&str, ModelPrice)] = &[
+    ("gpt-5", ModelPrice::new(1.25, Some(0.125), 10.0)),
+    ("gpt-5-mini", ModelPrice::new(0.25, Some(0.025), 2.0)),
+    ("gpt-5-nano", ModelPrice::new(0.05, Some(0.005), 0.4)),
+    ("gpt-4.5-preview", ModelPrice::new(75.0, Some(37.5), 150.0)),
+    ("gpt-4.1", ModelPrice::new(2.0, Some(0.5), 8.0)),
+    ("gpt-4.1-mini", ModelPrice::new(0.4, Some(0.1), 1.6)),
+    ("gpt-4.1-nano", ModelPrice::new(0.1, Some(0.025), 0.4)),
+    ("gpt-4o", ModelPrice::new(2.5, Some(1.25), 10.0)),
+    ("gpt-4o-2024-05-13", ModelPrice::new(5.0, None, 15.0)),
+    ("gpt-4o-mini", ModelPrice::new(0.15, Some(0.075), 0.6)),
+    ("chatgpt-4o-latest", ModelPrice::new(5.0, None, 15.0)),
+    ("o1", ModelPrice::new(15.0, Some(7.5), 60.0)),
+    ("o1-pro", ModelPrice::new(150.0, None, 600.0)),
+    ("o1-mini", ModelPrice::new(1.1, Some(0.55), 4.4)),
+    ("o3", ModelPrice::new(2.0, Some(0.5), 8.0)),
+    ("o3-mini", ModelPrice::new(1.1, Some(0.55), 4.4)),
+    ("o4-mini", ModelPrice::new(1.1, Some(0.275), 4.4)),
+    ("gpt-4-turbo", ModelPrice::new(10.0, None, 30.0)),
+    ("gpt-4-0125-preview", ModelPrice::new(10.0, None, 30.0)),
+    ("gpt-4-1106-preview", ModelPrice::new(10.0, None, 30.0)),
+    ("gpt-4", ModelPrice::new(30.0, None, 60.0)),
+    ("gpt-4-32k", ModelPrice::new(60.0, None, 120.0)),
+    ("gpt-3.5-turbo", ModelPrice::new(0.5, None, 1.5)),
+    ("gpt-3.5-turbo-0301", ModelPrice::new(1.5, None, 2.0)),
+    ("gpt-3.5-turbo-0613", ModelPrice::new(1.5, None, 2.0)),
+    ("gpt-3.5-turbo-1106", ModelPrice::new(1.0, None, 2.0)),
+    ("gpt-3.5-turbo-16k", ModelPrice::new(3.0, None, 4.0)),
+    ("gpt-3.5-turbo-16k-0613", ModelPrice::new(3.0, None, 4.0)),
+    ("gpt-3.5-turbo-instruct", ModelPrice::new(1.5, None, 2.0)),
+    ("davinci-002", ModelPrice::new(2.0, None, 2.0)),
+    ("babbage-002", ModelPrice::new(0.4, None, 0.4)),
+    ("text-embedding-3-small", ModelPrice::new(0.02, None, 0.0)),
+    ("text-embedding-3-large", ModelPrice::new(0.13, None, 0.0)),
+    ("text-embedding-ada-002", ModelPrice::new(0.1, None, 0.0)),
+];
+
+/// The price of a model, in US dollars per 1M tokens
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ModelPrice {
+    pub input: f64,
+    /// The discounted price of prompt tokens read from the prompt cache, `input` when `None`
+    #[serde(default)]
+    pub cached_input: Option<f64>,
+    #[serde(default)]
+    pub output: f64,
+}
+
+impl ModelPrice {
+    pub const fn new(input: f64, cached_input: Option<f64>, output: f64) -> Self {
+        ModelPrice {
+            input,
+            cached_input,
+            output,
+        }
+    }
+
+    /// The cost of the tokens counted in `usage`, in US dollars
+    pub fn cost(&self, usage: &Usage) -> f64 {
+        let cached = usage
+            .prompt_tokens_details
+            .as_ref()
+            .map_or(0, |details| details.cached_tokens)
+            .min(usage.prompt_tokens);
+        let uncached = usage.prompt_tokens - cached;
+
+        (uncached as f64 * self.input
+            + cached as f64 * self.cached_input.unwrap_or(self.input)
+            + usage.completion_tokens as f64 * self.output)
+            / 1_000_000.0
+    }
+}
+
+/// Finds the price of `model`, or of the model it is a dated snapshot of
+///
+/// Snapshots like `gpt-4o-2024-08-06` or `gpt-4-0613` are priced like their model unless they
+/// have a price of their own. Variants like `o3-pro` or `gpt-4o-audio-preview` aren't priced
+/// like `o3` or `gpt-4o`
+fn longest_match<'a>(
+    prices: impl Iterator<Item = (&'a str, &'a ModelPrice)>,
+    model: &str,
+) -> Option<ModelPrice> {
+    prices
+        .filter(|(id, _)| {
+            model
+                .strip_prefix(id)
+                .is_some_and(|rest| rest.is_empty() || is_snapshot_suffix(rest))
+        })
+        .max_by_key(|(id, _)| id.len())
+        .map(|(_, price)| *price)
+}
+
+/// Whether `suffix` is a snapshot date, either `-YYYY-MM-DD` or `-MMDD`
+fn is_snapshot_suffix(suffix: &str) -> bool {
+    let digits = |part: &str, len: usize| {
+        part.len() == len && part.bytes().all(|byte| byte.is_ascii_digit())
+    };
+
+    match suffix
+        .strip_prefix('-')
+        .map(|date| date.split('-').collect::<Vec<_>>())
+    {
+        Some(parts) => match parts.as_slice() {
+            [month_day] => digits(month_day, 4),
+            [year, month, day] => digits(year, 4) && digits(month, 2) && digits(day, 2),
+            _ => false,
+        },
+        None => false,
+    }
+}
+
+/// The built-in price of a model, see `PriceTable` to use other prices
+pub fn price(model: &str) -> Option<ModelPrice> {
+    longest_match(PRICES.iter().map(|(id, price)| (*id, price)), model)
+}
+
+/// Prices by model id, starting from the built-in ones
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(transparent)]
+pub struct PriceTable {
+    prices: HashMap<String, ModelPrice>,
+}
+
+impl Default for PriceTable {
+    fn default() -> Self {
+        PriceTable {
+            prices: PRICES
+                .iter()
+                .map(|(id, price)| (String::from(*id), *price))
+                .collect(),
+        }
+    }
+}
+
+impl PriceTable {
+    /// The built-in prices, overridden by those of a JSON file
+    ///
+    /// The file maps model ids to prices, e.g.
+    /// `{"gpt-4o": {"input": 2.5, "cached_input": 1.25, "output": 10.0}}`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _the file can't be read_
+    /// * _the file isn't a valid price table_
+    pub fn load<P: AsRef<Path>>(path: P) -> OpenAIResult<Self> {
+        let overrides: PriceTable = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        let mut table = PriceTable::default();
+        table.prices.extend(overrides.prices);
+
+        Ok(table)
+    }
+
+    /// Sets the price of a model, and of the snapshots starting with its id
+    pub fn with_price(mut self, model: impl Into<String>, price: ModelPrice) -> Self {
+        self.prices.insert(model.into(), price);
+        self
+    }
+
+    pub fn price(&self, model: &str) -> Option<ModelPrice> {
+        longest_match(
+            self.prices.iter().map(|(id, price)| (id.as_str(), price)),
+            model,
+        )
+    }
+
+    /// The cost of the tokens counted in `usage`, or `None` for models without a price
+    pub fn cost(&self, model: &str, usage: &Usage) -> Option<f64> {
+        self.price(model).map(|price| price.cost(usage))
+    }
+}
+
+impl Completion {
+    /// The cost of the completion at the built-in prices, in US dollars
+    ///
+    /// `None` when the model has no built-in price or the usage wasn't returned
+    pub fn estimated_cost(&self) -> Option<f64> {
+        Some(price(&self.model)?.cost(self.usage.as_ref()?))
+    }
+}
+
+impl ChatCompletion {
+    /// The cost of the completion at the built-in prices, in US dollars
+    ///
+    /// `None` when the model has no built-in price or the usage wasn't returned
+    pub fn estimated_cost(&self) -> Option<f64> {
+        Some(price(&self.model)?.cost(self.usage.as_ref()?))
+    }
+}
+
+impl Embeddings {
+    /// The cost of the embeddings at the built-in prices, in US dollars
+    ///
+    /// `None` when the model has no built-in price or the usage wasn't returned
+    pub fn estimated_cost(&self) -> Option<f64> {
+        Some(price(&self.model)?.cost(&Usage::from(self.usage.as_ref()?)))
+    }
+}
+
+#[derive(Debug, Default)]
+struct Spend {
+    total: f64,
+    by_model: HashMap<String, f64>,
+    by_user: HashMap<String, f64>,
+    /// Tokens used by models without a price
+    unpriced: HashMap<String, u64>,
+    /// The runs and responses already counted, which can be seen more than once
+    recorded: HashSet<String>,
+}
+
+/// Adds up the estimated cost of the completions, responses and runs made by a `Client`, see
+/// `Client::with_spend_tracker`
+///
+/// Clones share the same totals, so a clone kept aside can be read while the client is in use.
+/// Models missing from the price table can't be counted, their tokens are listed by `unpriced`
+/// instead. With a budget, requests to such models are rejected up front, and runs that turn out
+/// to use one are cancelled
+///
+/// ```no_run
+/// use openai_rust::pricing::SpendTracker;
+/// use openai_rust::Client;
+///
+/// let tracker = SpendTracker::new().with_budget(5.0);
+/// let client = Client::new(String::from("sk-...")).with_spend_tracker(tracker.clone());
+///
+/// // ... make requests with the client
+///
+/// println!("Spent ${:.4} so far", tracker.total());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SpendTracker {
+    prices: Arc<PriceTable>,
+    budget: Option<f64>,
+    spend: Arc<Mutex<Spend>>,
+}
+
+impl SpendTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Prices completions with the given table instead of the built-in prices
+    pub fn with_prices(mut self, prices: PriceTable) -> Self {
+        self.prices = Arc::new(prices);
+        self
+    }
+
+    /// Rejects requests with `OpenAIError::BudgetExceeded` once the total reaches `budget` US
+    /// dollars
+    ///
+    /// The request that crosses the budget still goes through, as its cost is only known once
+    /// it has been answered. Requests to models without a price are rejected with
+    /// `OpenAIError::UnpricedModel`, as they couldn't be counted against the budget
+    pub fn with_budget(mut self, budget: f64) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    fn spend(&self) -> std::sync::MutexGuard<'_, Spend> {
+        self.spend
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// The total spend so far, in US dollars
+    pub fn total(&self) -> f64 {
+        self.spend().total
+    }
+
+    /// The spend so far by the model ids returned with the completions
+    pub fn by_model(&self) -> HashMap<String, f64> {
+        self.spend().by_model.clone()
+    }
+
+    /// The spend so far by the `user` tag sent with the requests. Untagged requests aren't listed
+    pub fn by_user(&self) -> HashMap<String, f64> {
+        self.spend().by_user.clone()
+    }
+
+    /// The tokens used so far by models without a price, which aren't part of the totals
+    pub fn unpriced(&self) -> HashMap<String, u64> {
+        self.spend().unpriced.clone()
+    }
+
+    /// Adds the cost of `usage` to the totals, and returns it
+    ///
+    /// Returns `None` when the model has no price, in which case the tokens are added to
+    /// `unpriced`
+    pub fn record(&self, model: &str, user: Option<&str>, usage: &Usage) -> Option<f64> {
+        let mut spend = self.spend();
+        let Some(cost) = self.prices.cost(model, usage) else {
+            *spend.unpriced.entry(String::from(model)).or_default() += usage.total_tokens;
+            return None;
+        };
+
+        spend.total += cost;
+        *spend.by_model.entry(String::from(model)).or_default() += cost;
+        if let Some(user) = user {
+            *spend.by_user.entry(String::from(user)).or_default() += cost;
+        }
+
+        Some(cost)
+    }
+
+    /// Records the usage of a run or response only the first time its `id` is seen
+    pub(crate) fn record_once(
+        &self,
+        id: &str,
+        model: &str,
+        user: Option<&str>,
+        usage: &Usage,
+    ) -> Option<f64> {
+        if !self.spend().recorded.insert(String::from(id)) {
+            return None;
+        }
+
+        self.record(model, user, usage)
+    }
+
+    /// Tokens already used by models without a price don't count, they are only listed by
+    /// `unpriced`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _the total has reached the budget_
+    pub fn check_budget(&self) -> OpenAIResult<()> {
+        let Some(budget) = self.budget else {
+            return Ok(());
+        };
+        let spend = self.spend();
+        if spend.total >= budget {
+            return Err(OpenAIError::BudgetExceeded {
+                spent: spend.total,
+                budget,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _a budget is set and the model has no price_
+    pub fn check_price(&self, model: &str) -> OpenAIResult<()> {
+        match (self.budget, self.prices.price(model)) {
+            (Some(_), None) => Err(OpenAIError::UnpricedModel(String::from(model))),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(prompt_tokens: u64, cached_tokens: u64, completion_tokens: u64) -> Usage {
+        serde_json::from_value(serde_json::json!({
+            "prompt_tokens": prompt_tokens,
+            "completion_tokens": completion_tokens,
+            "total_tokens": prompt_tokens + completion_tokens,
+            "prompt_tokens_details": {"cached_tokens": cached_tokens},
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_cost_with_cached_tokens() {
+        let mini = price("gpt-4o-mini-2024-07-18").unwrap();
+        assert_eq!(mini, ModelPrice::new(0.15, Some(0.075), 0.6));
+
+        let cost = mini.cost(&usage(2_000_000, 1_000_000, 1_000_000));
+        assert!((cost - (0.15 + 0.075 + 0.6)).abs() < 1e-9);
+
+        assert_eq!(price("gpt-4-0613").unwrap().input, 30.0);
+        assert_eq!(price("gpt-4-turbo-2024-04-09").unwrap().input, 10.0);
+        assert_eq!(price("unknown-model"), None);
+    }
+
+    #[test]
+    fn test_variants_are_not_priced_as_their_base_model() {
+        assert_eq!(price("o3-2025-04-16"), price("o3"));
+        assert_eq!(price("o3-pro"), None);
+        assert_eq!(price("gpt-4o-realtime-preview"), None);
+        assert_eq!(price("gpt-4o-audio-preview-2024-12-17"), None);
+        assert_eq!(price("gpt-4o-2024-05-13").unwrap().input, 5.0);
+        assert_eq!(price("gpt-3.5-turbo-0125"), price("gpt-3.5-turbo"));
+        assert_eq!(price("gpt-3.5-turbo-16k").unwrap().input, 3.0);
+        assert_eq!(price("gpt-3.5-turbo-0613").unwrap().output, 2.0);
+        assert_eq!(price("gpt-4o-latest"), None);
+    }
+
+    #[test]
+    fn test_price_table_overrides() {
+        let path = std::env::temp_dir().join("openai-rust-test-prices.json");
+        std::fs::write(
+            &path,
+            r#"{"gpt-4o": {"input": 1.0, "output": 2.0}, "my-model": {"input": 3.0}}"#,
+        )
+        .unwrap();
+        let table = PriceTable::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            table.price("gpt-4o-2024-08-06"),
+            Some(ModelPrice::new(1.0, None, 2.0))
+        );
+        assert_eq!(
+            table.price("my-model"),
+            Some(ModelPrice::new(3.0, None, 0.0))
+        );
+        assert_eq!(table.price("gpt-4o-mini"), price("gpt-4o-mini"));
+    }
+
+    #[test]
+    fn test_spend_tracker_budget() {
+        let tracker = SpendTracker::new()
+            .with_prices(
+                PriceTable::default().with_price("test-model", ModelPrice::new(1.0, None, 1.0)),
+            )
+            .with_budget(1.0);
+        let shared = tracker.clone();
+
+        assert!(tracker.check_budget().is_ok());
+        tracker.record("test-model", Some("alice"), &usage(300_000, 0, 200_000));
+        tracker.record("test-model", None, &usage(500_000, 0, 0));
+        assert_eq!(
+            tracker.record("unknown-model", Some("alice"), &usage(1, 0, 1)),
+            None
+        );
+
+        assert!((shared.total() - 1.0).abs() < 1e-9);
+        assert!((shared.by_model()["test-model"] - 1.0).abs() < 1e-9);
+        assert!((shared.by_user()["alice"] - 0.5).abs() < 1e-9);
+        assert!(matches!(
+            shared.check_budget(),
+            Err(OpenAIError::BudgetExceeded { budget, .. }) if budget == 1.0
+        ));
+    }
+
+    #[test]
+    fn test_unpriced_models_with_budget() {
+        let unbudgeted = SpendTracker::new();
+        assert!(unbudgeted.check_price("my-model").is_ok());
+
+        let tracker = SpendTracker::new().with_budget(10.0);
+        assert!(tracker.check_price("gpt-4o").is_ok());
+        assert!(matches!(
+            tracker.check_price("my-model"),
+            Err(OpenAIError::UnpricedModel(model)) if model == "my-model"
+        ));
+
+        assert_eq!(tracker.record("my-model", None, &usage(100, 0, 20)), None);
+        assert_eq!(tracker.unpriced()["my-model"], 120);
+        // Earlier unpriced usage doesn't block requests to priced models
+        assert!(tracker.check_budget().is_ok());
+    }
+
+    #[test]
+    fn test_record_once() {
+        let tracker = SpendTracker::new();
+        let usage = usage(1_000_000, 0, 0);
+
+        assert!(tracker
+            .record_once("run_1", "gpt-4o", None, &usage)
+            .is_some());
+        assert!(tracker
+            .record_once("run_1", "gpt-4o", None, &usage)
+            .is_none());
+        assert!((tracker.total() - 2.5).abs() < 1e-9);
+    }
+}
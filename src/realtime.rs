@@ -0,0 +1,402 @@
+use crate::error::{OpenAIError, OpenAIResult};
+use crate::Client;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use futures::{Sink, Stream};
+use serde::{Deserialize, Serialize};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::{header::AUTHORIZATION, HeaderValue};
+use tokio_tungstenite::tungstenite::{self, Message};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+/// A WebSocket session with the Realtime API
+///
+/// Server events are read through its `Stream` implementation and client events are sent through
+/// its `Sink` implementation. Use `StreamExt::split` to read and write from separate tasks
+pub struct RealtimeSession {
+    ws: WebSocketStream<MaybeTlsStream<TcpStream>>,
+}
+
+impl RealtimeSession {
+    /// Opens a session against a specific URL, authenticated with the key of the client
+    ///
+    /// Mostly useful to talk to a proxy or a stand-in server, see `Client::connect_realtime`
+    pub async fn connect(client: &Client, url: &str) -> OpenAIResult<Self> {
+        let mut request = url.into_client_request()?;
+        let headers = request.headers_mut();
+
+        let auth_token = format!("Bearer {}", client.config.openai_secret_key);
+        headers.insert(AUTHORIZATION, header_value(&auth_token)?);
+        headers.insert("OpenAI-Beta", HeaderValue::from_static("realtime=v1"));
+        if let Some(org) = &client.config.openai_org {
+            headers.insert("OpenAI-Organization", header_value(org)?);
+        }
+
+        let (ws, _) = tokio_tungstenite::connect_async(request).await?;
+
+        Ok(RealtimeSession { ws })
+    }
+
+    /// Closes the WebSocket connection
+    pub async fn close(mut self) -> OpenAIResult<()> {
+        Ok(self.ws.close(None).await?)
+    }
+}
+
+fn header_value(value: &str) -> OpenAIResult<HeaderValue> {
+    HeaderValue::from_str(value).map_err(|e| tungstenite::Error::HttpFormat(e.into()).into())
+}
+
+impl Stream for RealtimeSession {
+    type Item = OpenAIResult<ServerEvent>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            let message = match Pin::new(&mut self.ws).poll_next(cx) {
+                Poll::Ready(Some(Ok(message))) => message,
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e.into()))),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            };
+
+            match message {
+                Message::Text(text) => {
+                    return Poll::Ready(Some(serde_json::from_str(&text).map_err(Into::into)))
+                }
+                Message::Close(_) => return Poll::Ready(None),
+                // Pings are answered by tungstenite, and the API never sends binary frames
+                _ => continue,
+            }
+        }
+    }
+}
+
+impl Sink<ClientEvent> for RealtimeSession {
+    type Error = OpenAIError;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<OpenAIResult<()>> {
+        Pin::new(&mut self.ws).poll_ready(cx).map_err(Into::into)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, event: ClientEvent) -> OpenAIResult<()> {
+        let message = Message::Text(serde_json::to_string(&event)?);
+
+        Ok(Pin::new(&mut self.ws).start_send(message)?)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<OpenAIResult<()>> {
+        Pin::new(&mut self.ws).poll_flush(cx).map_err(Into::into)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<OpenAIResult<()>> {
+        Pin::new(&mut self.ws).poll_close(cx).map_err(Into::into)
+    }
+}
+
+/// Events sent by the client
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum ClientEvent {
+    #[serde(rename = "session.update")]
+    SessionUpdate { session: SessionConfig },
+    /// Appends base64 encoded audio to the input buffer, see `ClientEvent::input_audio`
+    #[serde(rename = "input_audio_buffer.append")]
+    InputAudioBufferAppend { audio: String },
+    #[serde(rename = "input_audio_buffer.commit")]
+    InputAudioBufferCommit,
+    #[serde(rename = "input_audio_buffer.clear")]
+    InputAudioBufferClear,
+    #[serde(rename = "conversation.item.create")]
+    ConversationItemCreate { item: ConversationItem },
+    /// Asks the server for a response, optionally overriding the session config for it
+    #[serde(rename = "response.create")]
+    ResponseCreate {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        response: Option<SessionConfig>,
+    },
+    #[serde(rename = "response.cancel")]
+    ResponseCancel,
+}
+
+impl ClientEvent {
+    /// Appends raw audio, in the `input_audio_format` of the session, to the input buffer
+    pub fn input_audio(audio: &[u8]) -> Self {
+        ClientEvent::InputAudioBufferAppend {
+            audio: STANDARD.encode(audio),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct SessionConfig {
+    /// Any of `"text"` and `"audio"`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modalities: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub voice: Option<String>,
+    /// `"pcm16"`, `"g711_ulaw"` or `"g711_alaw"`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_audio_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_audio_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_audio_transcription: Option<serde_json::Value>,
+    /// Server side voice activity detection, `null` disables it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub turn_detection: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub tools: Vec<RealtimeTool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RealtimeTool {
+    Function {
+        name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+        parameters: serde_json::Value,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ConversationItem {
+    Message {
+        role: String,
+        content: Vec<ItemContent>,
+    },
+    FunctionCall {
+        call_id: String,
+        name: String,
+        arguments: String,
+    },
+    /// Answers a function call, follow up with `ClientEvent::ResponseCreate` to let the model
+    /// continue
+    FunctionCallOutput { call_id: String, output: String },
+}
+
+impl ConversationItem {
+    pub fn user_text(text: String) -> Self {
+        ConversationItem::Message {
+            role: String::from("user"),
+            content: vec![ItemContent::InputText { text }],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ItemContent {
+    InputText {
+        text: String,
+    },
+    InputAudio {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        audio: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        transcript: Option<String>,
+    },
+    Text {
+        text: String,
+    },
+    Audio {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        audio: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        transcript: Option<String>,
+    },
+}
+
+/// Events sent by the server
+///
+/// Sessions, items and responses are kept as raw JSON since the server reports many fields that
+/// the client never sets
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum ServerEvent {
+    #[serde(rename = "error")]
+    Error { error: RealtimeError },
+    #[serde(rename = "session.created")]
+    SessionCreated { session: serde_json::Value },
+    #[serde(rename = "session.updated")]
+    SessionUpdated { session: serde_json::Value },
+    #[serde(rename = "input_audio_buffer.speech_started")]
+    SpeechStarted {
+        audio_start_ms: u64,
+        item_id: String,
+    },
+    #[serde(rename = "input_audio_buffer.speech_stopped")]
+    SpeechStopped { audio_end_ms: u64, item_id: String },
+    #[serde(rename = "input_audio_buffer.committed")]
+    InputAudioBufferCommitted { item_id: String },
+    #[serde(rename = "conversation.item.created")]
+    ConversationItemCreated { item: serde_json::Value },
+    #[serde(rename = "response.created")]
+    ResponseCreated { response: serde_json::Value },
+    #[serde(rename = "response.done")]
+    ResponseDone { response: serde_json::Value },
+    #[serde(rename = "response.text.delta")]
+    TextDelta {
+        response_id: String,
+        item_id: String,
+        output_index: usize,
+        content_index: usize,
+        delta: String,
+    },
+    /// `delta` is base64 encoded audio, see `ServerEvent::audio_bytes`
+    #[serde(rename = "response.audio.delta")]
+    AudioDelta {
+        response_id: String,
+        item_id: String,
+        output_index: usize,
+        content_index: usize,
+        delta: String,
+    },
+    #[serde(rename = "response.audio_transcript.delta")]
+    AudioTranscriptDelta {
+        response_id: String,
+        item_id: String,
+        output_index: usize,
+        content_index: usize,
+        delta: String,
+    },
+    #[serde(rename = "response.function_call_arguments.delta")]
+    FunctionCallArgumentsDelta {
+        response_id: String,
+        item_id: String,
+        output_index: usize,
+        call_id: String,
+        delta: String,
+    },
+    #[serde(rename = "response.function_call_arguments.done")]
+    FunctionCallArgumentsDone {
+        response_id: String,
+        item_id: String,
+        output_index: usize,
+        call_id: String,
+        name: Option<String>,
+        arguments: String,
+    },
+    #[serde(rename = "rate_limits.updated")]
+    RateLimitsUpdated { rate_limits: Vec<serde_json::Value> },
+    /// Events this crate doesn't know about yet
+    #[serde(other)]
+    Other,
+}
+
+impl ServerEvent {
+    /// Decodes the audio carried by a `response.audio.delta` event
+    pub fn audio_bytes(&self) -> Option<Vec<u8>> {
+        match self {
+            ServerEvent::AudioDelta { delta, .. } => STANDARD.decode(delta).ok(),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct RealtimeError {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub code: Option<String>,
+    pub message: String,
+    pub param: Option<String>,
+    /// The client event that caused the error, if any
+    pub event_id: Option<String>,
+}
+
+impl Client {
+    /// Opens a Realtime API session for a model, authenticated with the key of this client
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _the WebSocket handshake fails, e.g. because the key is invalid_
+    pub async fn connect_realtime(&self, model: &str) -> OpenAIResult<RealtimeSession> {
+        RealtimeSession::connect(self, &self.realtime_url(model)?).await
+    }
+
+    /// The WebSocket counterpart of the API base, e.g. `wss://api.openai.com/v1/realtime`
+    fn realtime_url(&self, model: &str) -> OpenAIResult<String> {
+        let base = match self.api_base.split_once("://") {
+            Some(("http", rest)) => format!("ws://{}", rest),
+            Some((_, rest)) => format!("wss://{}", rest),
+            None => self.api_base.clone(),
+        };
+        let url =
+            reqwest::Url::parse_with_params(&format!("{}/realtime", base), [("model", model)])
+                .map_err(|e| OpenAIError::OtherError(format!("Invalid realtime URL: {}", e)))?;
+
+        Ok(url.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_realtime_url_follows_api_base() {
+        let client = Client::new(String::from("keystring"));
+        assert_eq!(
+            client.realtime_url("gpt-4o-realtime-preview").unwrap(),
+            "wss://api.openai.com/v1/realtime?model=gpt-4o-realtime-preview"
+        );
+
+        let proxied = client.with_api_base("http://127.0.0.1:8080/v1");
+        assert_eq!(
+            proxied.realtime_url("my model&x=1").unwrap(),
+            "ws://127.0.0.1:8080/v1/realtime?model=my+model%26x%3D1"
+        );
+    }
+
+    #[test]
+    fn test_client_event_serialization() {
+        let update = ClientEvent::SessionUpdate {
+            session: SessionConfig {
+                modalities: Some(vec![String::from("text")]),
+                instructions: Some(String::from("Be brief.")),
+                ..Default::default()
+            },
+        };
+        assert_eq!(
+            serde_json::to_string(&update).unwrap(),
+            r#"{"type":"session.update","session":{"modalities":["text"],"instructions":"Be brief."}}"#
+        );
+
+        assert_eq!(
+            serde_json::to_string(&ClientEvent::input_audio(&[0, 1, 2])).unwrap(),
+            r#"{"type":"input_audio_buffer.append","audio":"AAEC"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&ClientEvent::ResponseCreate { response: None }).unwrap(),
+            r#"{"type":"response.create"}"#
+        );
+    }
+
+    #[test]
+    fn test_server_event_deserialization() {
+        let event: ServerEvent = serde_json::from_str(
+            r#"{"event_id":"event_1","type":"response.audio.delta","response_id":"resp_1","item_id":"item_1","output_index":0,"content_index":0,"delta":"AAEC"}"#,
+        )
+        .unwrap();
+        assert_eq!(event.audio_bytes(), Some(vec![0, 1, 2]));
+
+        let error: ServerEvent = serde_json::from_str(
+            r#"{"event_id":"event_2","type":"error","error":{"type":"invalid_request_error","code":"invalid_value","message":"Invalid voice","param":"session.voice","event_id":"evt_9"}}"#,
+        )
+        .unwrap();
+        assert!(
+            matches!(error, ServerEvent::Error { error } if error.code.as_deref() == Some("invalid_value"))
+        );
+    }
+}
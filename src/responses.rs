@@ -0,0 +1,664 @@
+use crate::construct::{DeletionStatus, PromptTokensDetails, Usage};
+use crate::error::OpenAIResult;
+use crate::{Client, Streaming};
+use chrono::serde::ts_seconds_option;
+use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ResponseParams {
+    pub model: String,
+    pub input: ResponseInput,
+    #[serde(flatten)]
+    pub opts: OptResponseParams,
+}
+
+impl ResponseParams {
+    /// Continues the conversation of a previous response, so its input and output don't have to
+    /// be sent again
+    pub fn with_previous_response(mut self, response: &Response) -> Self {
+        self.opts.previous_response_id = Some(response.id.clone());
+        self
+    }
+}
+
+/// Either a plain text prompt, or a list of input items
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum ResponseInput {
+    Text(String),
+    Items(Vec<InputItem>),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InputItem {
+    Message {
+        role: InputRole,
+        content: Vec<InputContent>,
+    },
+    /// Echoes a function call made by the model in an earlier response
+    FunctionCall {
+        call_id: String,
+        name: String,
+        arguments: String,
+    },
+    /// The result of running a function call
+    FunctionCallOutput { call_id: String, output: String },
+    /// Passes the reasoning of an earlier response back to a reasoning model, e.g. when it isn't
+    /// continued through `previous_response_id`
+    Reasoning {
+        id: String,
+        summary: Vec<ReasoningSummary>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        encrypted_content: Option<String>,
+    },
+}
+
+impl InputItem {
+    /// A text message authored by the user
+    pub fn user_text(text: String) -> Self {
+        InputItem::Message {
+            role: InputRole::User,
+            content: vec![InputContent::InputText { text }],
+        }
+    }
+
+    /// An earlier reply of the assistant
+    pub fn assistant_text(text: String) -> Self {
+        InputItem::Message {
+            role: InputRole::Assistant,
+            content: vec![InputContent::OutputText { text }],
+        }
+    }
+
+    pub fn function_call_output(call_id: String, output: String) -> Self {
+        InputItem::FunctionCallOutput { call_id, output }
+    }
+}
+
+impl From<&Reasoning> for InputItem {
+    fn from(reasoning: &Reasoning) -> Self {
+        InputItem::Reasoning {
+            id: reasoning.id.clone(),
+            summary: reasoning.summary.clone(),
+            encrypted_content: reasoning.encrypted_content.clone(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum InputRole {
+    User,
+    Assistant,
+    System,
+    Developer,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InputContent {
+    InputText {
+        text: String,
+    },
+    /// The text of an assistant message, as returned in `OutputContent::OutputText`
+    OutputText {
+        text: String,
+    },
+    /// An image given either by URL (which may be a base64 data URL) or by uploaded file id
+    InputImage {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        image_url: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        file_id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        detail: Option<String>,
+    },
+    /// A file given either by uploaded file id or inline as base64 encoded data
+    InputFile {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        file_id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        file_data: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        filename: Option<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct OptResponseParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_response_id: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub tools: Vec<ResponseTool>,
+    /// `"none"`, `"auto"`, `"required"` or an object naming a specific tool
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parallel_tool_calls: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<ReasoningParams>,
+    /// Responses have to be stored to be referenced by `previous_response_id` later
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub store: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+}
+
+/// Tools the model may use while generating a response
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseTool {
+    Function {
+        name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+        parameters: serde_json::Value,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        strict: Option<bool>,
+    },
+    WebSearchPreview,
+    FileSearch {
+        vector_store_ids: Vec<String>,
+    },
+}
+
+/// Only supported by reasoning models
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct ReasoningParams {
+    /// `"low"`, `"medium"` or `"high"`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effort: Option<String>,
+    /// `"auto"`, `"concise"` or `"detailed"`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+}
+
+/// Response
+/// {
+/// "id": "resp_67ccd2bed1ec8190",
+/// "object": "response",
+/// "created_at": 1741476542,
+/// "status": "completed",
+/// "model": "gpt-4o-2024-08-06",
+/// "output": [...],
+/// "usage": {...}
+/// }
+#[derive(Deserialize, Debug)]
+pub struct Response {
+    pub id: String,
+    pub object: String,
+    #[serde(with = "ts_seconds_option")]
+    pub created_at: Option<DateTime<Utc>>,
+    pub status: ResponseStatus,
+    pub model: String,
+    #[serde(default)]
+    pub output: Vec<OutputItem>,
+    pub previous_response_id: Option<String>,
+    pub instructions: Option<String>,
+    pub error: Option<ResponseError>,
+    pub incomplete_details: Option<IncompleteDetails>,
+    pub usage: Option<ResponseUsage>,
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+impl Response {
+    /// Concatenates the text of every message in the output
+    pub fn output_text(&self) -> String {
+        self.output
+            .iter()
+            .filter_map(|item| match item {
+                OutputItem::Message(message) => Some(message.text()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The function calls the model wants us to run before it continues
+    pub fn function_calls(&self) -> Vec<&FunctionToolCall> {
+        self.output
+            .iter()
+            .filter_map(|item| match item {
+                OutputItem::FunctionCall(call) => Some(call),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseStatus {
+    Queued,
+    InProgress,
+    Completed,
+    Incomplete,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ResponseError {
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct IncompleteDetails {
+    /// `"max_output_tokens"` or `"content_filter"`
+    pub reason: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct ResponseUsage {
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    pub total_tokens: u32,
+    pub input_tokens_details: Option<InputTokensDetails>,
+    pub output_tokens_details: Option<OutputTokensDetails>,
+}
+
+impl From<&ResponseUsage> for Usage {
+    fn from(usage: &ResponseUsage) -> Self {
+        Usage {
+            prompt_tokens: usage.input_tokens as u64,
+            completion_tokens: usage.output_tokens as u64,
+            total_tokens: usage.total_tokens as u64,
+            prompt_tokens_details: usage.input_tokens_details.as_ref().map(|details| {
+                PromptTokensDetails {
+                    cached_tokens: details.cached_tokens as u64,
+                    ..PromptTokensDetails::default()
+                }
+            }),
+            ..Usage::default()
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct InputTokensDetails {
+    #[serde(default)]
+    pub cached_tokens: u32,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct OutputTokensDetails {
+    #[serde(default)]
+    pub reasoning_tokens: u32,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OutputItem {
+    Message(OutputMessage),
+    Reasoning(Reasoning),
+    FunctionCall(FunctionToolCall),
+    WebSearchCall(WebSearchCall),
+    /// Output items this crate doesn't know about yet
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct OutputMessage {
+    pub id: String,
+    pub role: String,
+    pub status: Option<String>,
+    pub content: Vec<OutputContent>,
+}
+
+impl OutputMessage {
+    pub fn text(&self) -> String {
+        self.content
+            .iter()
+            .filter_map(|part| match part {
+                OutputContent::OutputText { text, .. } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OutputContent {
+    OutputText {
+        text: String,
+        #[serde(default)]
+        annotations: Vec<serde_json::Value>,
+    },
+    Refusal {
+        refusal: String,
+    },
+    /// Content types this crate doesn't know about yet
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Reasoning {
+    pub id: String,
+    #[serde(default)]
+    pub summary: Vec<ReasoningSummary>,
+    /// Only returned when `reasoning.encrypted_content` is requested through `include`
+    pub encrypted_content: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ReasoningSummary {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub text: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct FunctionToolCall {
+    pub id: Option<String>,
+    /// Reference this id in the `FunctionCallOutput` input item that answers the call
+    pub call_id: String,
+    pub name: String,
+    pub arguments: String,
+    pub status: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct WebSearchCall {
+    pub id: String,
+    pub status: String,
+    pub action: Option<serde_json::Value>,
+}
+
+/// Semantic events sent while streaming a response
+///
+/// Every event also carries a `sequence_number`, which isn't kept since the events arrive in order
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type")]
+pub enum ResponseStreamEvent {
+    #[serde(rename = "response.created")]
+    Created { response: Response },
+    #[serde(rename = "response.in_progress")]
+    InProgress { response: Response },
+    #[serde(rename = "response.completed")]
+    Completed { response: Response },
+    #[serde(rename = "response.incomplete")]
+    Incomplete { response: Response },
+    #[serde(rename = "response.failed")]
+    Failed { response: Response },
+    #[serde(rename = "response.output_item.added")]
+    OutputItemAdded {
+        output_index: usize,
+        item: OutputItem,
+    },
+    #[serde(rename = "response.output_item.done")]
+    OutputItemDone {
+        output_index: usize,
+        item: OutputItem,
+    },
+    #[serde(rename = "response.output_text.delta")]
+    OutputTextDelta {
+        item_id: String,
+        output_index: usize,
+        content_index: usize,
+        delta: String,
+    },
+    #[serde(rename = "response.output_text.done")]
+    OutputTextDone {
+        item_id: String,
+        output_index: usize,
+        content_index: usize,
+        text: String,
+    },
+    #[serde(rename = "response.refusal.delta")]
+    RefusalDelta {
+        item_id: String,
+        output_index: usize,
+        content_index: usize,
+        delta: String,
+    },
+    #[serde(rename = "response.function_call_arguments.delta")]
+    FunctionCallArgumentsDelta {
+        item_id: String,
+        output_index: usize,
+        delta: String,
+    },
+    #[serde(rename = "response.function_call_arguments.done")]
+    FunctionCallArgumentsDone {
+        item_id: String,
+        output_index: usize,
+        arguments: String,
+    },
+    #[serde(rename = "response.reasoning_summary_text.delta")]
+    ReasoningSummaryTextDelta {
+        item_id: String,
+        output_index: usize,
+        delta: String,
+    },
+    #[serde(rename = "error")]
+    Error {
+        code: Option<String>,
+        message: String,
+        param: Option<String>,
+    },
+    /// Events this crate doesn't know about yet
+    #[serde(other)]
+    Other,
+}
+
+impl Client {
+    /// Generates a model response from text, image or file inputs, optionally continuing an
+    /// earlier response through `previous_response_id`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _the budget of the spend tracker is spent_
+    /// * _the requested model doesn't exist_
+    /// * _endpoint is unavailable_
+    /// * _deserialization of JSON Response data fails_
+    #[tokio::main]
+    pub async fn create_response(&self, params: ResponseParams) -> OpenAIResult<Response> {
+        self.check_budget(Some(&params.model))?;
+        let url = format!("{}/responses", self.api_base);
+
+        let response: Response = self.send(self.http_client.post(url).json(&params)).await?;
+        self.record_spend(
+            &response.model,
+            params.opts.user.as_deref(),
+            response.usage.as_ref().map(Usage::from).as_ref(),
+        );
+
+        Ok(response)
+    }
+
+    /// Retrieves a stored response by its id
+    #[tokio::main]
+    pub async fn retrieve_response(&self, response_id: &str) -> OpenAIResult<Response> {
+        let url = format!("{}/responses/{}", self.api_base, response_id);
+
+        self.send(self.http_client.get(url)).await
+    }
+
+    #[tokio::main]
+    pub async fn delete_response(&self, response_id: &str) -> OpenAIResult<DeletionStatus> {
+        let url = format!("{}/responses/{}", self.api_base, response_id);
+
+        self.send(self.http_client.delete(url)).await
+    }
+
+    /// Generates a model response and streams its semantic events as they happen
+    ///
+    /// Unlike the other methods of `Client` this doesn't block: the request is sent once the
+    /// stream is first polled, which has to happen inside a Tokio runtime
+    pub fn create_response_stream(
+        &self,
+        params: ResponseParams,
+    ) -> impl Stream<Item = OpenAIResult<ResponseStreamEvent>> {
+        let url = format!("{}/responses", self.api_base);
+        let request = self.http_client.post(url).json(&Streaming::new(&params));
+        let spend_tracker = self.spend_tracker();
+        let user = params.opts.user.clone();
+
+        let events = self
+            .send_stream(request)
+            .map(|event| event.and_then(|event| Ok(serde_json::from_str(&event.data)?)))
+            .inspect(move |event| {
+                let response = match event {
+                    Ok(ResponseStreamEvent::Completed { response })
+                    | Ok(ResponseStreamEvent::Incomplete { response })
+                    | Ok(ResponseStreamEvent::Failed { response }) => response,
+                    _ => return,
+                };
+                if let (Some(spend_tracker), Some(usage)) = (&spend_tracker, &response.usage) {
+                    spend_tracker.record_once(
+                        &response.id,
+                        &response.model,
+                        user.as_deref(),
+                        &Usage::from(usage),
+                    );
+                }
+            });
+
+        self.budgeted(Some(&params.model), events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_params_serialization() {
+        let params = ResponseParams {
+            model: String::from("gpt-4o"),
+            input: ResponseInput::Items(vec![
+                InputItem::Message {
+                    role: InputRole::User,
+                    content: vec![
+                        InputContent::InputText {
+                            text: String::from("What is in this image?"),
+                        },
+                        InputContent::InputImage {
+                            image_url: Some(String::from("https://example.com/cat.png")),
+                            file_id: None,
+                            detail: None,
+                        },
+                    ],
+                },
+                InputItem::function_call_output(String::from("call_123"), String::from("42")),
+            ]),
+            opts: OptResponseParams {
+                previous_response_id: Some(String::from("resp_123")),
+                ..Default::default()
+            },
+        };
+
+        let params_serialized = serde_json::to_string(&params).unwrap();
+
+        let params_json = r#"{"model":"gpt-4o","input":[{"type":"message","role":"user","content":[{"type":"input_text","text":"What is in this image?"},{"type":"input_image","image_url":"https://example.com/cat.png"}]},{"type":"function_call_output","call_id":"call_123","output":"42"}],"previous_response_id":"resp_123"}"#;
+
+        assert_eq!(params_serialized, params_json);
+    }
+
+    #[test]
+    fn test_response_deserialization() {
+        let response_data = r#"
+        {
+            "id": "resp_123",
+            "object": "response",
+            "created_at": 1741476542,
+            "status": "completed",
+            "model": "o4-mini-2025-04-16",
+            "output": [
+                { "type": "reasoning", "id": "rs_1", "summary": [{ "type": "summary_text", "text": "Checked the weather." }] },
+                { "type": "web_search_call", "id": "ws_1", "status": "completed" },
+                {
+                    "type": "function_call",
+                    "id": "fc_1",
+                    "call_id": "call_1",
+                    "name": "get_weather",
+                    "arguments": "{\"city\":\"Paris\"}",
+                    "status": "completed"
+                },
+                {
+                    "type": "message",
+                    "id": "msg_1",
+                    "role": "assistant",
+                    "status": "completed",
+                    "content": [
+                        { "type": "output_text", "text": "It is sunny.", "annotations": [] },
+                        { "type": "output_audio", "transcript": "It is sunny." }
+                    ]
+                },
+                { "type": "image_generation_call", "id": "ig_1" }
+            ],
+            "previous_response_id": null,
+            "usage": {
+                "input_tokens": 36,
+                "input_tokens_details": { "cached_tokens": 0 },
+                "output_tokens": 87,
+                "output_tokens_details": { "reasoning_tokens": 64 },
+                "total_tokens": 123
+            }
+        }"#;
+
+        let response: Response = serde_json::from_str(response_data).unwrap();
+
+        assert_eq!(response.status, ResponseStatus::Completed);
+        assert_eq!(response.output_text(), "It is sunny.");
+        assert_eq!(response.function_calls()[0].call_id, "call_1");
+        assert!(matches!(response.output[4], OutputItem::Unknown));
+        assert!(matches!(
+            &response.output[3],
+            OutputItem::Message(message) if matches!(message.content[1], OutputContent::Other)
+        ));
+
+        let OutputItem::Reasoning(reasoning) = &response.output[0] else {
+            panic!("expected reasoning, got {:?}", response.output[0]);
+        };
+        let follow_up = ResponseInput::Items(vec![
+            InputItem::from(reasoning),
+            InputItem::assistant_text(response.output_text()),
+        ]);
+        assert_eq!(
+            serde_json::to_string(&follow_up).unwrap(),
+            r#"[{"type":"reasoning","id":"rs_1","summary":[{"type":"summary_text","text":"Checked the weather."}]},{"type":"message","role":"assistant","content":[{"type":"output_text","text":"It is sunny."}]}]"#
+        );
+        assert_eq!(
+            response
+                .usage
+                .unwrap()
+                .output_tokens_details
+                .unwrap()
+                .reasoning_tokens,
+            64
+        );
+    }
+
+    #[test]
+    fn test_response_stream_event_deserialization() {
+        let delta: ResponseStreamEvent = serde_json::from_str(
+            r#"{"type":"response.output_text.delta","item_id":"msg_1","output_index":0,"content_index":0,"delta":"Hi","sequence_number":4}"#,
+        )
+        .unwrap();
+        assert!(
+            matches!(delta, ResponseStreamEvent::OutputTextDelta { delta, .. } if delta == "Hi")
+        );
+
+        let unknown: ResponseStreamEvent =
+            serde_json::from_str(r#"{"type":"response.audio.delta","delta":"AAA="}"#).unwrap();
+        assert!(matches!(unknown, ResponseStreamEvent::Other));
+    }
+}
@@ -0,0 +1,283 @@
+use crate::construct::{FinishReason, ToolCall};
+use crate::error::{OpenAIError, OpenAIResult};
+use crate::param::{ChatParams, FunctionDefinition, Message, Tool};
+use crate::Client;
+use futures::future::{join_all, LocalBoxFuture};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::future::Future;
+
+type Handler = Box<dyn Fn(String) -> LocalBoxFuture<'static, Result<String, String>>>;
+
+/// Drives a chat completion through its tool calls
+///
+/// Each turn the model is called with the registered tools. The tool calls it asks for are run
+/// concurrently with their handlers and answered with `Message::tool` messages, until the model
+/// stops with a final answer
+///
+/// ```no_run
+/// use openai_rust::param::{ChatParams, FunctionDefinition, Message, OptChatParams};
+/// use openai_rust::runner::ToolRunner;
+/// use openai_rust::Client;
+///
+/// let client = Client::new(String::from("sk-..."));
+/// let get_time = FunctionDefinition {
+///     name: String::from("get_time"),
+///     description: Some(String::from("Gets the current UTC time")),
+///     parameters: None,
+///     strict: None,
+/// };
+/// let runner = ToolRunner::new().register(get_time, |_arguments| async {
+///     Ok::<_, String>(String::from("12:00"))
+/// });
+///
+/// let transcript = runner
+///     .run(
+///         &client,
+///         ChatParams {
+///             model: String::from("gpt-4o-mini"),
+///             messages: vec![Message::user(String::from("What time is it?"))],
+///             opt: OptChatParams::default(),
+///         },
+///     )
+///     .unwrap();
+/// ```
+pub struct ToolRunner {
+    tools: Vec<Tool>,
+    handlers: HashMap<String, Handler>,
+    max_iterations: usize,
+}
+
+impl Default for ToolRunner {
+    fn default() -> Self {
+        Self {
+            tools: Vec::new(),
+            handlers: HashMap::new(),
+            max_iterations: 10,
+        }
+    }
+}
+
+impl ToolRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how many times the model may be called before giving up, 10 by default
+    pub fn max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    /// Registers a function tool along with the handler that runs it
+    ///
+    /// The handler receives the raw JSON `arguments` generated by the model. An error is sent
+    /// back to the model as the tool output, so that it can correct itself
+    ///
+    /// Registering a function under the name of an earlier one replaces it
+    pub fn register<F, Fut, E>(mut self, function: FunctionDefinition, handler: F) -> Self
+    where
+        F: Fn(String) -> Fut + 'static,
+        Fut: Future<Output = Result<String, E>> + 'static,
+        E: Display,
+    {
+        self.handlers.insert(
+            function.name.clone(),
+            Box::new(move |arguments| {
+                let output = handler(arguments);
+                Box::pin(async move { output.await.map_err(|e| e.to_string()) })
+            }),
+        );
+        self.tools.retain(|tool| tool_name(tool) != function.name);
+        self.tools.push(Tool::Function { function });
+        self
+    }
+
+    /// Registers a tool described by a Rust type, whose handler receives the parsed arguments
+    #[cfg(feature = "schemars")]
+    pub fn register_tool<T, F, Fut, E>(self, handler: F) -> Self
+    where
+        T: crate::tools::OpenAITool + 'static,
+        F: Fn(T) -> Fut + 'static,
+        Fut: Future<Output = Result<String, E>> + 'static,
+        E: Display + 'static,
+    {
+        self.register(T::definition(), move |arguments| {
+            let output = T::from_arguments(&arguments).map(&handler);
+            async move {
+                match output {
+                    Ok(output) => output.await.map_err(|e| e.to_string()),
+                    Err(e) => Err(e.to_string()),
+                }
+            }
+        })
+    }
+
+    /// Runs the conversation until the model answers without calling a tool
+    ///
+    /// The registered tools are added to `chat_params`, replacing the tools of the same name. The
+    /// returned transcript starts with the given messages and ends with the final assistant reply
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _any chat completion request fails_
+    /// * _a reply is cut off by `max_tokens` or the context length_
+    /// * _the model stops for any other reason than a final answer, e.g. the content filter_
+    /// * _the model is still calling tools after `max_iterations` turns_
+    #[tokio::main]
+    pub async fn run(
+        &self,
+        client: &Client,
+        chat_params: ChatParams,
+    ) -> OpenAIResult<Vec<Message>> {
+        self.drive(client, chat_params).await
+    }
+
+    async fn drive(
+        &self,
+        client: &Client,
+        mut chat_params: ChatParams,
+    ) -> OpenAIResult<Vec<Message>> {
+        chat_params
+            .opt
+            .tools
+            .retain(|tool| !self.handlers.contains_key(tool_name(tool)));
+        chat_params.opt.tools.extend(self.tools.iter().cloned());
+
+        for _ in 0..self.max_iterations {
+            let completion = client.chat_completion(&chat_params).await?;
+            let (message, finish_reason) = match completion.choices.into_iter().next() {
+                Some(choice) => (choice.message, choice.finish_reason),
+                None => return Err(OpenAIError::UnknownError),
+            };
+            let tool_calls = message.tool_calls().to_vec();
+            chat_params.messages.push(message);
+
+            match finish_reason {
+                // Tool calls cut off by `max_tokens` have incomplete arguments
+                Some(FinishReason::Length) => return Err(OpenAIError::Truncated),
+                _ if !tool_calls.is_empty() => {
+                    let outputs = self.call_tools(&tool_calls).await;
+                    chat_params.messages.extend(outputs);
+                }
+                Some(FinishReason::Stop) => return Ok(chat_params.messages),
+                finish_reason => return Err(OpenAIError::UnexpectedFinishReason(finish_reason)),
+            }
+        }
+
+        Err(OpenAIError::MaxIterationsReached {
+            iterations: self.max_iterations,
+            transcript: chat_params.messages,
+        })
+    }
+
+    /// Runs the tool calls concurrently, answering each one in order
+    async fn call_tools(&self, tool_calls: &[ToolCall]) -> Vec<Message> {
+        join_all(tool_calls.iter().map(|tool_call| async move {
+            let output = match self.handlers.get(&tool_call.function.name) {
+                Some(handler) => handler(tool_call.function.arguments.clone()).await,
+                None => Err(format!("unknown tool `{}`", tool_call.function.name)),
+            };
+            let content = output.unwrap_or_else(|e| format!("Error: {}", e));
+
+            Message::tool(tool_call.id.clone(), content)
+        }))
+        .await
+    }
+}
+
+fn tool_name(tool: &Tool) -> &str {
+    match tool {
+        Tool::Function { function } => &function.name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn function(name: &str) -> FunctionDefinition {
+        FunctionDefinition {
+            name: String::from(name),
+            description: None,
+            parameters: None,
+            strict: None,
+        }
+    }
+
+    fn tool_call(id: &str, name: &str, arguments: &str) -> ToolCall {
+        ToolCall::function(
+            String::from(id),
+            String::from(name),
+            String::from(arguments),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_tool_calls_run_concurrently() {
+        let runner = ToolRunner::new().register(function("sleep"), |arguments| async move {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            Ok::<_, String>(arguments)
+        });
+
+        let started = tokio::time::Instant::now();
+        let outputs = runner
+            .call_tools(&[
+                tool_call("call_1", "sleep", "1"),
+                tool_call("call_2", "sleep", "2"),
+                tool_call("call_3", "sleep", "3"),
+            ])
+            .await;
+
+        assert!(started.elapsed() < Duration::from_millis(500));
+        assert_eq!(
+            outputs,
+            vec![
+                Message::tool(String::from("call_1"), String::from("1")),
+                Message::tool(String::from("call_2"), String::from("2")),
+                Message::tool(String::from("call_3"), String::from("3")),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tool_errors_are_reported_to_model() {
+        let runner = ToolRunner::new().register(function("fail"), |_| async {
+            Err::<String, _>("city not found")
+        });
+
+        let outputs = runner
+            .call_tools(&[
+                tool_call("call_1", "fail", "{}"),
+                tool_call("call_2", "missing", "{}"),
+            ])
+            .await;
+
+        assert_eq!(outputs[0].text(), "Error: city not found");
+        assert_eq!(outputs[1].text(), "Error: unknown tool `missing`");
+    }
+
+    #[tokio::test]
+    async fn test_registering_a_name_again_replaces_the_tool() {
+        let runner = ToolRunner::new()
+            .register(function("lookup"), |_| async {
+                Ok::<_, String>(String::from("old"))
+            })
+            .register(function("other"), |_| async {
+                Ok::<_, String>(String::new())
+            })
+            .register(function("lookup"), |_| async {
+                Ok::<_, String>(String::from("new"))
+            });
+
+        let names: Vec<&str> = runner.tools.iter().map(tool_name).collect();
+        assert_eq!(names, vec!["other", "lookup"]);
+
+        let outputs = runner
+            .call_tools(&[tool_call("call_1", "lookup", "{}")])
+            .await;
+        assert_eq!(outputs[0].text(), "new");
+    }
+}
@@ -0,0 +1,662 @@
+use crate::assistants::AssistantTool;
+use crate::construct::{Page, ToolCall, Usage};
+use crate::error::{APIErrorData, OpenAIResult};
+use crate::param::ListParams;
+use crate::pricing::SpendTracker;
+use crate::sse::SseEvent;
+use crate::threads::{MessageDelta, Thread, ThreadMessage};
+use crate::{Client, Streaming};
+use chrono::serde::ts_seconds_option;
+use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Run
+/// {
+/// "id": "run_abc123",
+/// "object": "thread.run",
+/// "created_at": 1699063290,
+/// "assistant_id": "asst_abc123",
+/// "thread_id": "thread_abc123",
+/// "status": "queued",
+/// "model": "gpt-4o",
+/// ...
+/// }
+#[derive(Deserialize, Debug)]
+pub struct Run {
+    pub id: String,
+    pub object: String,
+    #[serde(with = "ts_seconds_option")]
+    pub created_at: Option<DateTime<Utc>>,
+    pub thread_id: String,
+    pub assistant_id: String,
+    pub status: RunStatus,
+    pub required_action: Option<RequiredAction>,
+    pub last_error: Option<RunError>,
+    #[serde(default, with = "ts_seconds_option")]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(default, with = "ts_seconds_option")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(default, with = "ts_seconds_option")]
+    pub cancelled_at: Option<DateTime<Utc>>,
+    #[serde(default, with = "ts_seconds_option")]
+    pub failed_at: Option<DateTime<Utc>>,
+    #[serde(default, with = "ts_seconds_option")]
+    pub completed_at: Option<DateTime<Utc>>,
+    pub model: String,
+    pub instructions: Option<String>,
+    #[serde(default)]
+    pub tools: Vec<AssistantTool>,
+    pub metadata: Option<HashMap<String, String>>,
+    pub usage: Option<Usage>,
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub max_prompt_tokens: Option<u32>,
+    pub max_completion_tokens: Option<u32>,
+    pub parallel_tool_calls: Option<bool>,
+}
+
+impl Run {
+    /// The tool calls the run is waiting on, if its status is `requires_action`
+    pub fn pending_tool_calls(&self) -> &[ToolCall] {
+        match &self.required_action {
+            Some(RequiredAction::SubmitToolOutputs {
+                submit_tool_outputs,
+            }) => &submit_tool_outputs.tool_calls,
+            None => &[],
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Queued,
+    InProgress,
+    RequiresAction,
+    Cancelling,
+    Cancelled,
+    Failed,
+    Completed,
+    Incomplete,
+    Expired,
+    /// Statuses this crate doesn't know about yet
+    #[serde(other)]
+    Unknown,
+}
+
+impl RunStatus {
+    /// Whether the run is still being worked on by the API without any input from us
+    ///
+    /// Unknown statuses count as pending, so polling carries on until the run reaches a known
+    /// status or the poll timeout passes
+    pub fn is_pending(&self) -> bool {
+        matches!(
+            self,
+            RunStatus::Queued | RunStatus::InProgress | RunStatus::Cancelling | RunStatus::Unknown
+        )
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RequiredAction {
+    SubmitToolOutputs {
+        submit_tool_outputs: SubmitToolOutputs,
+    },
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SubmitToolOutputs {
+    pub tool_calls: Vec<ToolCall>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RunError {
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RunParams {
+    pub assistant_id: String,
+    #[serde(flatten)]
+    pub opts: OptRunParams,
+}
+
+/// Overrides of the assistant configuration for a single run
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct OptRunParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub additional_instructions: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<AssistantTool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_prompt_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_completion_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parallel_tool_calls: Option<bool>,
+}
+
+/// The result of executing one of the tool calls of a `requires_action` run
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ToolOutput {
+    pub tool_call_id: String,
+    pub output: String,
+}
+
+#[derive(Serialize, Debug)]
+struct SubmitToolOutputsParams {
+    tool_outputs: Vec<ToolOutput>,
+}
+
+/// Events sent while streaming a run
+#[derive(Debug)]
+pub enum RunStreamEvent {
+    /// `thread.created`
+    ThreadCreated(Thread),
+    /// Any of the `thread.run.*` status changes, e.g. `thread.run.requires_action`
+    RunUpdated(Box<Run>),
+    /// Any of the `thread.run.step.*` events
+    RunStep {
+        event: String,
+        data: serde_json::Value,
+    },
+    /// `thread.message.created`, `thread.message.in_progress`, `thread.message.completed` or
+    /// `thread.message.incomplete`
+    MessageUpdated(ThreadMessage),
+    /// `thread.message.delta`
+    MessageDelta(MessageDelta),
+    /// `error`
+    Error(APIErrorData),
+    /// `done`, the last event of the stream
+    Done,
+    /// Events this crate doesn't know about yet
+    Other { event: String, data: String },
+}
+
+impl RunStreamEvent {
+    fn from_sse(sse: SseEvent) -> OpenAIResult<Self> {
+        let event = sse.event.unwrap_or_default();
+
+        let parsed = match event.as_str() {
+            "done" => RunStreamEvent::Done,
+            "error" => RunStreamEvent::Error(serde_json::from_str(&sse.data)?),
+            "thread.created" => RunStreamEvent::ThreadCreated(serde_json::from_str(&sse.data)?),
+            "thread.message.delta" => {
+                RunStreamEvent::MessageDelta(serde_json::from_str(&sse.data)?)
+            }
+            e if e.starts_with("thread.run.step.") => RunStreamEvent::RunStep {
+                data: serde_json::from_str(&sse.data)?,
+                event,
+            },
+            e if e.starts_with("thread.run.") => {
+                RunStreamEvent::RunUpdated(serde_json::from_str(&sse.data)?)
+            }
+            e if e.starts_with("thread.message.") => {
+                RunStreamEvent::MessageUpdated(serde_json::from_str(&sse.data)?)
+            }
+            _ => RunStreamEvent::Other {
+                event,
+                data: sse.data,
+            },
+        };
+
+        Ok(parsed)
+    }
+}
+
+impl Client {
+    fn runs_url(&self, thread_id: &str) -> String {
+        format!("{}/threads/{}/runs", self.api_base, thread_id)
+    }
+
+    fn run_url(&self, thread_id: &str, run_id: &str) -> String {
+        format!("{}/threads/{}/runs/{}", self.api_base, thread_id, run_id)
+    }
+}
+
+fn run_events(
+    events: impl Stream<Item = OpenAIResult<SseEvent>>,
+) -> impl Stream<Item = OpenAIResult<RunStreamEvent>> {
+    events.map(|event| event.and_then(RunStreamEvent::from_sse))
+}
+
+impl Client {
+    /// Starts a run of an assistant on a thread
+    ///
+    /// The run is queued and returned straight away, use `poll_run` or `create_and_poll_run` to
+    /// wait for it to finish
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _the budget of the spend tracker is spent_
+    /// * _the thread or assistant doesn't exist_
+    /// * _endpoint is unavailable_
+    /// * _deserialization of JSON Run data fails_
+    /// * _the run uses a model without a price under a budget, in which case it is cancelled_
+    #[tokio::main]
+    pub async fn create_run(&self, thread_id: &str, params: RunParams) -> OpenAIResult<Run> {
+        self.check_budget(params.opts.model.as_deref())?;
+        let run: Run = self
+            .send(
+                self.beta_request(Method::POST, self.runs_url(thread_id))
+                    .json(&params),
+            )
+            .await?;
+
+        self.check_run_price(run).await
+    }
+
+    #[tokio::main]
+    pub async fn retrieve_run(&self, thread_id: &str, run_id: &str) -> OpenAIResult<Run> {
+        self.send(self.beta_request(Method::GET, self.run_url(thread_id, run_id)))
+            .await
+    }
+
+    #[tokio::main]
+    pub async fn list_runs(&self, thread_id: &str, params: ListParams) -> OpenAIResult<Page<Run>> {
+        self.send(
+            self.beta_request(Method::GET, self.runs_url(thread_id))
+                .query(&params),
+        )
+        .await
+    }
+
+    /// Lazily walks every run of a thread, fetching further pages as needed
+    ///
+    /// The stream has to be polled inside a Tokio runtime
+    pub fn list_all_runs(
+        &self,
+        thread_id: &str,
+        params: ListParams,
+    ) -> impl Stream<Item = OpenAIResult<Run>> + '_ {
+        let url = self.runs_url(thread_id);
+
+        self.paginate(
+            move |query| self.beta_request(Method::GET, url.clone()).query(query),
+            params,
+        )
+    }
+
+    #[tokio::main]
+    pub async fn cancel_run(&self, thread_id: &str, run_id: &str) -> OpenAIResult<Run> {
+        let url = format!("{}/cancel", self.run_url(thread_id, run_id));
+
+        self.send(self.beta_request(Method::POST, url)).await
+    }
+
+    /// Sends the results of the tool calls a `requires_action` run is waiting on
+    ///
+    /// A resumed run whose model has no price is cancelled when the client has a budget, see
+    /// `create_run`
+    #[tokio::main]
+    pub async fn submit_tool_outputs(
+        &self,
+        thread_id: &str,
+        run_id: &str,
+        tool_outputs: Vec<ToolOutput>,
+    ) -> OpenAIResult<Run> {
+        self.check_budget(None)?;
+        let url = format!("{}/submit_tool_outputs", self.run_url(thread_id, run_id));
+        let params = SubmitToolOutputsParams { tool_outputs };
+        let run: Run = self
+            .send(self.beta_request(Method::POST, url).json(&params))
+            .await?;
+
+        self.check_run_price(run).await
+    }
+
+    /// Blocks until the run leaves the `queued`, `in_progress` and `cancelling` states, checking
+    /// its status once every `interval`
+    ///
+    /// The returned run is either finished or `requires_action`, in which case the tool calls are
+    /// available through `Run::pending_tool_calls`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _the run is still pending once the poll timeout of the client passes, see
+    ///   `Client::with_poll_timeout`_
+    /// * _endpoint is unavailable_
+    #[tokio::main]
+    pub async fn poll_run(
+        &self,
+        thread_id: &str,
+        run_id: &str,
+        interval: Duration,
+    ) -> OpenAIResult<Run> {
+        self.wait_for_run(thread_id, run_id, interval).await
+    }
+
+    /// Starts a run and blocks until it finishes or requires action, see `poll_run`
+    #[tokio::main]
+    pub async fn create_and_poll_run(
+        &self,
+        thread_id: &str,
+        params: RunParams,
+        interval: Duration,
+    ) -> OpenAIResult<Run> {
+        self.check_budget(params.opts.model.as_deref())?;
+        let run: Run = self
+            .send(
+                self.beta_request(Method::POST, self.runs_url(thread_id))
+                    .json(&params),
+            )
+            .await?;
+        let run = self.check_run_price(run).await?;
+
+        self.wait_for_run(thread_id, &run.id, interval).await
+    }
+
+    /// Sends tool outputs and blocks until the run finishes or requires action again
+    #[tokio::main]
+    pub async fn submit_tool_outputs_and_poll(
+        &self,
+        thread_id: &str,
+        run_id: &str,
+        tool_outputs: Vec<ToolOutput>,
+        interval: Duration,
+    ) -> OpenAIResult<Run> {
+        self.check_budget(None)?;
+        let url = format!("{}/submit_tool_outputs", self.run_url(thread_id, run_id));
+        let params = SubmitToolOutputsParams { tool_outputs };
+        let run: Run = self
+            .send(self.beta_request(Method::POST, url).json(&params))
+            .await?;
+        let run = self.check_run_price(run).await?;
+
+        self.wait_for_run(thread_id, &run.id, interval).await
+    }
+
+    /// Starts a run and streams its events as they happen
+    ///
+    /// Unlike the other methods of `Client` this doesn't block: the request is sent once the
+    /// stream is first polled, which has to happen inside a Tokio runtime. When the client has a
+    /// budget and the run uses a model without a price, the stream yields
+    /// `OpenAIError::UnpricedModel` and the run should be cancelled with `cancel_run`
+    pub fn create_run_stream(
+        &self,
+        thread_id: &str,
+        params: RunParams,
+    ) -> impl Stream<Item = OpenAIResult<RunStreamEvent>> {
+        let request = self
+            .beta_request(Method::POST, self.runs_url(thread_id))
+            .json(&Streaming::new(&params));
+
+        self.budgeted(
+            params.opts.model.as_deref(),
+            self.recorded(run_events(self.send_stream(request))),
+        )
+    }
+
+    /// Sends tool outputs and streams the events of the resumed run, see `create_run_stream`
+    pub fn submit_tool_outputs_stream(
+        &self,
+        thread_id: &str,
+        run_id: &str,
+        tool_outputs: Vec<ToolOutput>,
+    ) -> impl Stream<Item = OpenAIResult<RunStreamEvent>> {
+        let url = format!("{}/submit_tool_outputs", self.run_url(thread_id, run_id));
+        let params = SubmitToolOutputsParams { tool_outputs };
+        let request = self
+            .beta_request(Method::POST, url)
+            .json(&Streaming::new(&params));
+
+        self.budgeted(None, self.recorded(run_events(self.send_stream(request))))
+    }
+
+    async fn wait_for_run(
+        &self,
+        thread_id: &str,
+        run_id: &str,
+        interval: Duration,
+    ) -> OpenAIResult<Run> {
+        self.before_poll_timeout(async {
+            loop {
+                let run: Run = self
+                    .send(self.beta_request(Method::GET, self.run_url(thread_id, run_id)))
+                    .await?;
+
+                if !run.status.is_pending() {
+                    self.record_run_spend(&run);
+                    return Ok(run);
+                }
+                tokio::time::sleep(interval).await;
+            }
+        })
+        .await
+    }
+
+    /// Adds the usage of a finished run to the spend tracker, only the first time it is seen
+    fn record_run_spend(&self, run: &Run) {
+        record_run(self.spend_tracker().as_ref(), run);
+    }
+
+    /// Cancels a run whose model, e.g. one inherited from the assistant, has no price to count
+    /// against the budget
+    async fn check_run_price(&self, run: Run) -> OpenAIResult<Run> {
+        if let Err(unpriced) = self.check_budget(Some(&run.model)) {
+            let url = format!("{}/cancel", self.run_url(&run.thread_id, &run.id));
+            // The run is given up on either way, so a failed cancellation isn't reported
+            let _ = self.send::<Run>(self.beta_request(Method::POST, url)).await;
+
+            return Err(unpriced);
+        }
+
+        Ok(run)
+    }
+
+    /// Records the usage of the runs that finish while the events are streamed, and fails on runs
+    /// whose model has no price to count against the budget
+    fn recorded(
+        &self,
+        events: impl Stream<Item = OpenAIResult<RunStreamEvent>>,
+    ) -> impl Stream<Item = OpenAIResult<RunStreamEvent>> {
+        let spend_tracker = self.spend_tracker();
+
+        events.map(move |event| {
+            if let (Ok(RunStreamEvent::RunUpdated(run)), Some(spend_tracker)) =
+                (&event, &spend_tracker)
+            {
+                spend_tracker.check_price(&run.model)?;
+                record_run(Some(spend_tracker), run);
+            }
+            event
+        })
+    }
+}
+
+/// Runs only report their usage once they reach a terminal status
+fn record_run(spend_tracker: Option<&SpendTracker>, run: &Run) {
+    if let (Some(spend_tracker), Some(usage)) = (spend_tracker, &run.usage) {
+        if !run.status.is_pending() && run.status != RunStatus::RequiresAction {
+            spend_tracker.record_once(&run.id, &run.model, None, usage);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::OpenAIError;
+
+    #[test]
+    fn test_requires_action_run_deserialization() {
+        let run_data = r#"
+        {
+            "id": "run_abc123",
+            "object": "thread.run",
+            "created_at": 1699075592,
+            "assistant_id": "asst_abc123",
+            "thread_id": "thread_abc123",
+            "status": "requires_action",
+            "required_action": {
+                "type": "submit_tool_outputs",
+                "submit_tool_outputs": {
+                    "tool_calls": [
+                        {
+                            "id": "call_abc123",
+                            "type": "function",
+                            "function": { "name": "get_stock_price", "arguments": "{\"symbol\":\"AAPL\"}" }
+                        }
+                    ]
+                }
+            },
+            "last_error": null,
+            "expires_at": 1699076192,
+            "started_at": 1699075592,
+            "cancelled_at": null,
+            "failed_at": null,
+            "completed_at": null,
+            "model": "gpt-4o",
+            "instructions": "You tell the current stock price.",
+            "tools": [
+                { "type": "function", "function": { "name": "get_stock_price", "parameters": {} } }
+            ],
+            "metadata": {},
+            "usage": null,
+            "temperature": 1.0,
+            "top_p": 1.0,
+            "max_prompt_tokens": 1000,
+            "max_completion_tokens": 1000,
+            "parallel_tool_calls": true
+        }"#;
+
+        let run: Run = serde_json::from_str(run_data).unwrap();
+
+        assert_eq!(run.status, RunStatus::RequiresAction);
+        assert!(!run.status.is_pending());
+        assert_eq!(run.pending_tool_calls().len(), 1);
+        assert_eq!(run.pending_tool_calls()[0].function.name, "get_stock_price");
+    }
+
+    #[test]
+    fn test_run_stream_event_parsing() {
+        let delta = RunStreamEvent::from_sse(SseEvent {
+            event: Some(String::from("thread.message.delta")),
+            data: String::from(
+                r#"{"id":"msg_123","object":"thread.message.delta","delta":{"content":[{"index":0,"type":"text","text":{"value":"Hi"}}]}}"#,
+            ),
+        })
+        .unwrap();
+        assert!(matches!(delta, RunStreamEvent::MessageDelta(d) if d.text() == "Hi"));
+
+        let step = RunStreamEvent::from_sse(SseEvent {
+            event: Some(String::from("thread.run.step.created")),
+            data: String::from(r#"{"id":"step_123"}"#),
+        })
+        .unwrap();
+        assert!(
+            matches!(step, RunStreamEvent::RunStep { event, .. } if event == "thread.run.step.created")
+        );
+
+        let done = RunStreamEvent::from_sse(SseEvent {
+            event: Some(String::from("done")),
+            data: String::from("[DONE]"),
+        })
+        .unwrap();
+        assert!(matches!(done, RunStreamEvent::Done));
+    }
+
+    fn run_json(status: &str, model: &str) -> String {
+        format!(
+            r#"{{"id":"run_abc123","object":"thread.run","created_at":1699075592,"thread_id":"thread_abc123","assistant_id":"asst_abc123","status":"{}","required_action":null,"last_error":null,"model":"{}","instructions":null,"metadata":{{}},"usage":null,"temperature":null,"top_p":null,"max_prompt_tokens":null,"max_completion_tokens":null,"parallel_tool_calls":null}}"#,
+            status, model
+        )
+    }
+
+    #[test]
+    fn test_run_with_unpriced_inherited_model_is_cancelled() {
+        let (requests, received) = std::sync::mpsc::channel();
+        let api_base = crate::stand_in::stand_in_server(move |request| {
+            let request_line = request.lines().next().unwrap_or_default();
+            requests.send(String::from(request_line)).unwrap();
+            let status = if request_line.contains("/cancel") {
+                "cancelling"
+            } else {
+                "queued"
+            };
+            run_json(status, "my-model")
+        });
+        let spend_tracker = SpendTracker::new().with_budget(1.0);
+        let client = Client::new(String::from("keystring"))
+            .with_api_base(api_base)
+            .with_spend_tracker(spend_tracker.clone());
+
+        let params = RunParams {
+            assistant_id: String::from("asst_abc123"),
+            opts: OptRunParams::default(),
+        };
+        let result = client.create_run("thread_abc123", params);
+
+        assert!(matches!(result, Err(OpenAIError::UnpricedModel(model)) if model == "my-model"));
+        let requests: Vec<String> = received.try_iter().collect();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].starts_with("POST /v1/threads/thread_abc123/runs/run_abc123/cancel"));
+
+        // The client stays usable for priced models
+        spend_tracker.record(
+            "my-model",
+            None,
+            &serde_json::from_str(
+                r#"{"prompt_tokens":10,"completion_tokens":5,"total_tokens":15}"#,
+            )
+            .unwrap(),
+        );
+        assert!(client.check_budget(Some("gpt-4o")).is_ok());
+    }
+
+    #[test]
+    fn test_poll_run_gives_up_after_poll_timeout() {
+        let api_base = crate::stand_in::stand_in_server(|_| run_json("in_progress", "gpt-4o"));
+        let client = Client::new(String::from("keystring"))
+            .with_api_base(api_base)
+            .with_poll_timeout(Duration::from_millis(200));
+
+        let result = client.poll_run("thread_abc123", "run_abc123", Duration::from_millis(20));
+
+        assert!(matches!(
+            result,
+            Err(OpenAIError::PollTimeout(timeout)) if timeout == Duration::from_millis(200)
+        ));
+    }
+
+    #[test]
+    fn test_unknown_run_status_is_pending() {
+        let run: Run = serde_json::from_str(&run_json("paused", "gpt-4o")).unwrap();
+
+        assert_eq!(run.status, RunStatus::Unknown);
+        assert!(run.status.is_pending());
+    }
+
+    #[test]
+    fn test_run_params_streaming_serialization() {
+        let params = RunParams {
+            assistant_id: String::from("asst_abc123"),
+            opts: OptRunParams::default(),
+        };
+
+        let params_serialized = serde_json::to_string(&Streaming::new(&params)).unwrap();
+
+        assert_eq!(
+            params_serialized,
+            r#"{"assistant_id":"asst_abc123","stream":true}"#
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use crate::error::OpenAIResult;
+use futures::{Stream, StreamExt};
+use reqwest::Response;
+use std::collections::VecDeque;
+
+/// A single dispatched server-sent event
+#[derive(Debug, PartialEq)]
+pub(crate) struct SseEvent {
+    pub event: Option<String>,
+    pub data: String,
+}
+
+/// Incremental parser for the `text/event-stream` format
+///
+/// Bytes are buffered until a full line is available so multi-byte characters split across
+/// network chunks are decoded correctly
+#[derive(Debug, Default)]
+pub(crate) struct SseParser {
+    buf: Vec<u8>,
+    event: Option<String>,
+    data: Vec<String>,
+}
+
+impl SseParser {
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buf.extend_from_slice(chunk);
+
+        let mut events = Vec::new();
+        while let Some(pos) = self.buf.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buf.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            if line.is_empty() {
+                if let Some(event) = self.dispatch() {
+                    events.push(event);
+                }
+                continue;
+            }
+
+            let (field, value) = match line.split_once(':') {
+                Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+                None => (line, ""),
+            };
+
+            match field {
+                "event" => self.event = Some(value.to_string()),
+                "data" => self.data.push(value.to_string()),
+                // Comments (empty field name), ids and retry hints carry nothing we use
+                _ => {}
+            }
+        }
+
+        events
+    }
+
+    /// Flushes an event left pending when the connection closes without a trailing blank line
+    pub fn finish(&mut self) -> Option<SseEvent> {
+        self.feed(b"\n");
+        self.dispatch()
+    }
+
+    fn dispatch(&mut self) -> Option<SseEvent> {
+        let event = self.event.take();
+        if self.data.is_empty() {
+            return None;
+        }
+
+        Some(SseEvent {
+            event,
+            data: std::mem::take(&mut self.data).join("\n"),
+        })
+    }
+}
+
+/// Turns the body of a streaming response into a stream of events
+pub(crate) fn events(resp: Response) -> impl Stream<Item = OpenAIResult<SseEvent>> {
+    let state = (
+        Box::pin(resp.bytes_stream()),
+        SseParser::default(),
+        VecDeque::new(),
+        false,
+    );
+
+    futures::stream::unfold(
+        state,
+        |(mut body, mut parser, mut pending, mut closed)| async move {
+            loop {
+                if let Some(event) = pending.pop_front() {
+                    return Some((Ok(event), (body, parser, pending, closed)));
+                }
+                if closed {
+                    return None;
+                }
+
+                match body.next().await {
+                    Some(Ok(chunk)) => pending.extend(parser.feed(&chunk)),
+                    Some(Err(e)) => {
+                        closed = true;
+                        return Some((Err(e.into()), (body, parser, pending, closed)));
+                    }
+                    None => {
+                        closed = true;
+                        pending.extend(parser.finish());
+                    }
+                }
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sse_parser_events() {
+        let mut parser = SseParser::default();
+        let events = parser.feed(
+            b"event: thread.run.created\ndata: {\"id\":\"run_1\"}\n\n: keep-alive\n\ndata: [DONE]\n\n",
+        );
+
+        assert_eq!(
+            events,
+            vec![
+                SseEvent {
+                    event: Some(String::from("thread.run.created")),
+                    data: String::from("{\"id\":\"run_1\"}"),
+                },
+                SseEvent {
+                    event: None,
+                    data: String::from("[DONE]"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sse_parser_split_chunks() {
+        let mut parser = SseParser::default();
+
+        assert!(parser.feed(b"data: {\"text\":\"caf\xc3").is_empty());
+        assert!(parser.feed(b"\xa9\"}\r\ndata: second line").is_empty());
+
+        let event = parser.finish().unwrap();
+        assert_eq!(event.event, None);
+        assert_eq!(event.data, "{\"text\":\"café\"}\nsecond line");
+    }
+}
@@ -0,0 +1,59 @@
+//! A stand-in for the API in unit tests, answering over plain HTTP on a local port
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+/// Answers every request with the JSON body that `respond` returns for it, until the tests end
+///
+/// `respond` receives the whole request, head and body. Returns the base URL to pass to
+/// `Client::with_api_base`
+pub(crate) fn stand_in_server<F>(mut respond: F) -> String
+where
+    F: FnMut(&str) -> String + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let api_base = format!("http://{}/v1", listener.local_addr().unwrap());
+
+    std::thread::spawn(move || {
+        for socket in listener.incoming() {
+            let mut socket = socket.unwrap();
+            let body = respond(&read_request(&mut socket));
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).unwrap();
+        }
+    });
+
+    api_base
+}
+
+/// Reads one request, up to the end of the body announced by its `content-length`
+fn read_request(socket: &mut TcpStream) -> String {
+    let mut request = Vec::new();
+    let mut buf = [0; 4096];
+    loop {
+        let n = socket.read(&mut buf).unwrap();
+        if n == 0 {
+            return String::from_utf8_lossy(&request).into_owned();
+        }
+        request.extend_from_slice(&buf[..n]);
+
+        let text = String::from_utf8_lossy(&request);
+        if let Some(end) = text.find("\r\n\r\n") {
+            let length = text[..end]
+                .lines()
+                .find_map(|line| {
+                    let line = line.to_ascii_lowercase();
+                    line.strip_prefix("content-length:")
+                        .map(|length| length.trim().parse::<usize>().unwrap())
+                })
+                .unwrap_or(0);
+            if request.len() >= end + 4 + length {
+                return text.into_owned();
+            }
+        }
+    }
+}
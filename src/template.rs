@@ -0,0 +1,440 @@
+use crate::error::{OpenAIError, OpenAIResult};
+use crate::param::{Message, Role};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Variable(String),
+    /// Repeated for every item of a list, or kept when the variable is `true`
+    Section {
+        name: String,
+        body: Vec<Node>,
+    },
+    /// Starts a new chat message
+    Role(Role),
+}
+
+/// A prompt with `{{variable}}` placeholders, filled in from any `Serialize` value
+///
+/// * `{{name}}` is replaced with the variable. Strings are inserted as they are, other values as JSON
+/// * `{{#name}}...{{/name}}` repeats its body for every object in the list `name`, where the
+///   fields of the object can be used as variables. It is rendered once when `name` is `true`, and
+///   left out when it is `false` or an empty list
+/// * `{{@user}}` starts a new message with the given role, see `render_messages`
+/// * `\{{` is rendered as a literal `{{`
+///
+/// ```
+/// use openai_rust::param::CompletionParams;
+/// use openai_rust::template::PromptTemplate;
+/// use serde_json::json;
+///
+/// let template = PromptTemplate::parse(
+///     "Translate to French.\n{{#examples}}{{en}} => {{fr}}\n{{/examples}}{{text}} =>",
+/// )
+/// .unwrap();
+/// let prompt = template
+///     .render(&json!({
+///         "examples": [{"en": "cat", "fr": "chat"}, {"en": "dog", "fr": "chien"}],
+///         "text": "bird",
+///     }))
+///     .unwrap();
+/// assert_eq!(prompt, "Translate to French.\ncat => chat\ndog => chien\nbird =>");
+///
+/// let completion_params = CompletionParams::builder("gpt-3.5-turbo-instruct")
+///     .prompt(prompt)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptTemplate {
+    nodes: Vec<Node>,
+}
+
+impl PromptTemplate {
+    /// Parses a template
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _a placeholder isn't closed with `}}`_
+    /// * _a placeholder isn't a valid variable name, section or role_
+    /// * _a section isn't closed, or is closed in the wrong place_
+    pub fn parse(source: &str) -> OpenAIResult<Self> {
+        // The sections being parsed, each with the nodes parsed so far
+        let mut stack: Vec<(Option<String>, Vec<Node>)> = vec![(None, Vec::new())];
+        let mut text = String::new();
+        let mut rest = source;
+
+        while let Some(start) = rest.find("{{") {
+            if rest[..start].ends_with('\\') {
+                text.push_str(&rest[..start - 1]);
+                text.push_str("{{");
+                rest = &rest[start + 2..];
+                continue;
+            }
+            text.push_str(&rest[..start]);
+            let end = rest[start..]
+                .find("}}")
+                .map(|end| start + end)
+                .ok_or_else(|| {
+                    template_error(format!("unclosed placeholder at `{}`", &rest[start..]))
+                })?;
+            let tag = rest[start + 2..end].trim();
+            rest = &rest[end + 2..];
+
+            let nodes = &mut stack.last_mut().expect("the root is never popped").1;
+            if !text.is_empty() {
+                nodes.push(Node::Text(std::mem::take(&mut text)));
+            }
+            if let Some(name) = tag.strip_prefix('#') {
+                stack.push((Some(variable_name(name)?), Vec::new()));
+            } else if let Some(name) = tag.strip_prefix('/') {
+                let name = variable_name(name)?;
+                match stack.pop() {
+                    Some((Some(open), body)) if open == name => {
+                        let nodes = &mut stack.last_mut().expect("the root is never popped").1;
+                        nodes.push(Node::Section { name, body });
+                    }
+                    _ => return Err(template_error(format!("unexpected `{{{{/{}}}}}`", name))),
+                }
+            } else if let Some(role) = tag.strip_prefix('@') {
+                nodes.push(Node::Role(role_name(role)?));
+            } else {
+                nodes.push(Node::Variable(variable_name(tag)?));
+            }
+        }
+        text.push_str(rest);
+
+        match stack.pop() {
+            Some((None, mut nodes)) => {
+                if !text.is_empty() {
+                    nodes.push(Node::Text(text));
+                }
+                Ok(PromptTemplate { nodes })
+            }
+            Some((Some(name), _)) => Err(template_error(format!("unclosed `{{{{#{}}}}}`", name))),
+            None => unreachable!("the root is never popped"),
+        }
+    }
+
+    /// Reads and parses a template file
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _the file can't be read_
+    /// * _the file isn't a valid template, see `parse`_
+    pub fn from_file<P: AsRef<Path>>(path: P) -> OpenAIResult<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// The names of the variables the template expects, in the order they first appear
+    pub fn variables(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        for node in &self.nodes {
+            match node {
+                Node::Variable(name) | Node::Section { name, .. }
+                    if !names.contains(&name.as_str()) =>
+                {
+                    names.push(name.as_str())
+                }
+                _ => {}
+            }
+        }
+        names
+    }
+
+    /// Renders the template into a single prompt, such as `CompletionParams::prompt`
+    ///
+    /// `variables` must serialize into a map, like a struct, a `HashMap` or a `json!` object
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _any variable is missing or `null`, as `OpenAIError::MissingVariables`_
+    /// * _`variables` or a section item isn't a map_
+    /// * _the template contains role markers_
+    pub fn render<T: Serialize>(&self, variables: &T) -> OpenAIResult<String> {
+        match self.render_segments(variables)?.as_slice() {
+            [] => Ok(String::new()),
+            [(None, text)] => Ok(text.clone()),
+            _ => Err(template_error(String::from(
+                "role markers can only be rendered with `render_messages`",
+            ))),
+        }
+    }
+
+    /// Renders the template into chat messages, starting a new message at every role marker
+    ///
+    /// The content of each message is trimmed of surrounding whitespace
+    ///
+    /// ```
+    /// use openai_rust::param::Message;
+    /// use openai_rust::template::PromptTemplate;
+    /// use serde_json::json;
+    ///
+    /// let template = PromptTemplate::parse(
+    ///     "{{@system}}\nAnswer in {{language}}.\n{{@user}}\n{{question}}\n",
+    /// )
+    /// .unwrap();
+    /// let messages = template
+    ///     .render_messages(&json!({"language": "French", "question": "How are you?"}))
+    ///     .unwrap();
+    ///
+    /// assert_eq!(
+    ///     messages,
+    ///     vec![
+    ///         Message::system(String::from("Answer in French.")),
+    ///         Message::user(String::from("How are you?")),
+    ///     ]
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _any variable is missing or `null`, as `OpenAIError::MissingVariables`_
+    /// * _`variables` or a section item isn't a map_
+    /// * _there is text before the first role marker_
+    pub fn render_messages<T: Serialize>(&self, variables: &T) -> OpenAIResult<Vec<Message>> {
+        self.render_segments(variables)?
+            .into_iter()
+            .filter(|(role, text)| role.is_some() || !text.trim().is_empty())
+            .map(|(role, text)| match role {
+                Some(role) => Ok(message(role, String::from(text.trim()))),
+                None => Err(template_error(String::from(
+                    "text before the first role marker",
+                ))),
+            })
+            .collect()
+    }
+
+    /// Renders the template into runs of text, each following the role marker that started it
+    fn render_segments<T: Serialize>(
+        &self,
+        variables: &T,
+    ) -> OpenAIResult<Vec<(Option<Role>, String)>> {
+        let variables = match serde_json::to_value(variables)? {
+            Value::Object(variables) => variables,
+            _ => return Err(template_error(String::from("variables must be a map"))),
+        };
+
+        let mut renderer = Renderer {
+            segments: Vec::new(),
+            missing: Vec::new(),
+        };
+        renderer.render(&self.nodes, &mut vec![&variables])?;
+        if !renderer.missing.is_empty() {
+            return Err(OpenAIError::MissingVariables(renderer.missing));
+        }
+
+        Ok(renderer.segments)
+    }
+}
+
+struct Renderer {
+    segments: Vec<(Option<Role>, String)>,
+    missing: Vec<String>,
+}
+
+impl Renderer {
+    fn render<'a>(
+        &mut self,
+        nodes: &'a [Node],
+        scopes: &mut Vec<&'a Map<String, Value>>,
+    ) -> OpenAIResult<()> {
+        for node in nodes {
+            match node {
+                Node::Text(text) => self.push(text),
+                Node::Role(role) => self.segments.push((Some(*role), String::new())),
+                Node::Variable(name) => match lookup(scopes, name) {
+                    Some(Value::String(text)) => self.push(text),
+                    Some(value) => self.push(&value.to_string()),
+                    None => self.missing(name),
+                },
+                Node::Section { name, body } => match lookup(scopes, name) {
+                    Some(Value::Bool(true)) => self.render(body, scopes)?,
+                    Some(Value::Bool(false)) => {}
+                    Some(Value::Array(items)) => {
+                        for item in items {
+                            let Value::Object(item) = item else {
+                                return Err(template_error(format!(
+                                    "the items of section `{}` must be maps",
+                                    name
+                                )));
+                            };
+                            scopes.push(item);
+                            self.render(body, scopes)?;
+                            scopes.pop();
+                        }
+                    }
+                    Some(_) => {
+                        return Err(template_error(format!(
+                            "section `{}` must be a list or a boolean",
+                            name
+                        )))
+                    }
+                    None => self.missing(name),
+                },
+            }
+        }
+
+        Ok(())
+    }
+
+    fn push(&mut self, text: &str) {
+        match self.segments.last_mut() {
+            Some((_, segment)) => segment.push_str(text),
+            None => self.segments.push((None, String::from(text))),
+        }
+    }
+
+    fn missing(&mut self, name: &str) {
+        if !self.missing.iter().any(|missing| missing == name) {
+            self.missing.push(String::from(name));
+        }
+    }
+}
+
+/// Finds a variable in the innermost section that has it, treating `null` as missing
+fn lookup<'a>(scopes: &[&'a Map<String, Value>], name: &str) -> Option<&'a Value> {
+    scopes
+        .iter()
+        .rev()
+        .find_map(|scope| scope.get(name))
+        .filter(|value| !value.is_null())
+}
+
+fn variable_name(name: &str) -> OpenAIResult<String> {
+    let name = name.trim();
+    if !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        Ok(String::from(name))
+    } else {
+        Err(template_error(format!("invalid variable name `{}`", name)))
+    }
+}
+
+fn role_name(role: &str) -> OpenAIResult<Role> {
+    match role.trim() {
+        "developer" => Ok(Role::Developer),
+        "system" => Ok(Role::System),
+        "user" => Ok(Role::User),
+        "assistant" => Ok(Role::Assistant),
+        role => Err(template_error(format!("invalid role `{}`", role))),
+    }
+}
+
+fn message(role: Role, content: String) -> Message {
+    match role {
+        Role::Developer => Message::developer(content),
+        Role::System => Message::system(content),
+        Role::Assistant => Message::assistant(content),
+        _ => Message::user(content),
+    }
+}
+
+fn template_error(message: String) -> OpenAIError {
+    OpenAIError::TemplateError(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_render_variables_and_escapes() {
+        #[derive(Serialize)]
+        struct Review<'a> {
+            product: &'a str,
+            stars: u8,
+            verified: Option<bool>,
+        }
+
+        let template =
+            PromptTemplate::parse(r"Review of {{ product }} ({{stars}}/5), \{{not a variable}}")
+                .unwrap();
+        assert_eq!(template.variables(), vec!["product", "stars"]);
+
+        let review = Review {
+            product: "kettle",
+            stars: 4,
+            verified: None,
+        };
+        assert_eq!(
+            template.render(&review).unwrap(),
+            "Review of kettle (4/5), {{not a variable}}"
+        );
+
+        let template = PromptTemplate::parse("{{product}} {{verified}} {{price}}").unwrap();
+        match template.render(&review) {
+            Err(OpenAIError::MissingVariables(missing)) => {
+                assert_eq!(missing, vec!["verified", "price"])
+            }
+            other => panic!("expected missing variables, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for source in [
+            "Hello {{name",
+            "{{#examples}}{{input}}",
+            "{{input}}{{/examples}}",
+            "{{#a}}{{#b}}{{/a}}{{/b}}",
+            "{{first name}}",
+            "{{@narrator}}",
+        ] {
+            assert!(
+                matches!(
+                    PromptTemplate::parse(source),
+                    Err(OpenAIError::TemplateError(_))
+                ),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn test_render_few_shot_messages() {
+        let template = PromptTemplate::parse(
+            "{{@system}} Classify the sentiment.
+{{#examples}}
+{{@user}} {{text}}
+{{@assistant}} {{label}}
+{{/examples}}
+{{@user}} {{text}}",
+        )
+        .unwrap();
+
+        let messages = template
+            .render_messages(&json!({
+                "examples": [
+                    {"text": "I love it", "label": "positive"},
+                    {"text": "It broke", "label": "negative"},
+                ],
+                "text": "Works fine",
+            }))
+            .unwrap();
+        assert_eq!(
+            messages,
+            vec![
+                Message::system(String::from("Classify the sentiment.")),
+                Message::user(String::from("I love it")),
+                Message::assistant(String::from("positive")),
+                Message::user(String::from("It broke")),
+                Message::assistant(String::from("negative")),
+                Message::user(String::from("Works fine")),
+            ]
+        );
+
+        assert!(matches!(
+            template.render(&json!({"examples": [], "text": "Works fine"})),
+            Err(OpenAIError::TemplateError(_))
+        ));
+    }
+}
@@ -0,0 +1,421 @@
+use crate::assistants::{AssistantTool, ToolResources};
+use crate::construct::{DeletionStatus, Page};
+use crate::error::OpenAIResult;
+use crate::param::ListParams;
+use crate::Client;
+use chrono::serde::ts_seconds_option;
+use chrono::{DateTime, Utc};
+use futures::Stream;
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Thread
+/// {
+/// "id": "thread_abc123",
+/// "object": "thread",
+/// "created_at": 1698107661,
+/// "metadata": {}
+/// }
+#[derive(Deserialize, Debug)]
+pub struct Thread {
+    pub id: String,
+    pub object: String,
+    #[serde(with = "ts_seconds_option")]
+    pub created_at: Option<DateTime<Utc>>,
+    pub tool_resources: Option<ToolResources>,
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ThreadParams {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub messages: Vec<MessageParams>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_resources: Option<ToolResources>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ModifyThreadParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_resources: Option<ToolResources>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+/// Only users and assistants author messages on a thread
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageRole {
+    User,
+    Assistant,
+}
+
+/// Message
+/// {
+/// "id": "msg_abc123",
+/// "object": "thread.message",
+/// "created_at": 1698983503,
+/// "thread_id": "thread_abc123",
+/// "role": "assistant",
+/// "content": [...],
+/// "assistant_id": "asst_abc123",
+/// "run_id": "run_abc123"
+/// }
+#[derive(Deserialize, Debug)]
+pub struct ThreadMessage {
+    pub id: String,
+    pub object: String,
+    #[serde(with = "ts_seconds_option")]
+    pub created_at: Option<DateTime<Utc>>,
+    pub thread_id: String,
+    pub status: Option<String>,
+    pub role: MessageRole,
+    pub content: Vec<MessageContent>,
+    pub assistant_id: Option<String>,
+    pub run_id: Option<String>,
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+impl ThreadMessage {
+    /// Concatenates the text parts of the message, skipping images and refusals
+    pub fn text(&self) -> String {
+        self.content
+            .iter()
+            .filter_map(|part| match part {
+                MessageContent::Text { text } => Some(text.value.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MessageContent {
+    Text { text: Text },
+    ImageFile { image_file: ImageFile },
+    ImageUrl { image_url: ImageUrl },
+    Refusal { refusal: String },
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Text {
+    pub value: String,
+    /// File citations and file paths referenced by the text
+    #[serde(default)]
+    pub annotations: Vec<serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ImageFile {
+    pub file_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ImageUrl {
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// A file attached to a message, along with the tools it should be added to
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Attachment {
+    pub file_id: String,
+    #[serde(default)]
+    pub tools: Vec<AssistantTool>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MessageParams {
+    pub role: MessageRole,
+    pub content: String,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub attachments: Vec<Attachment>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+impl MessageParams {
+    /// Creates a plain text message authored by the user
+    pub fn user(content: String) -> Self {
+        MessageParams {
+            role: MessageRole::User,
+            content,
+            attachments: Vec::new(),
+            metadata: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ModifyMessageParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+/// Incremental content of a message, as sent by `thread.message.delta` stream events
+#[derive(Deserialize, Debug)]
+pub struct MessageDelta {
+    pub id: String,
+    pub object: String,
+    pub delta: MessageDeltaContent,
+}
+
+impl MessageDelta {
+    /// Concatenates the text fragments carried by this delta
+    pub fn text(&self) -> String {
+        self.delta
+            .content
+            .iter()
+            .filter_map(|part| match part {
+                MessageDeltaPart::Text { text, .. } => text.value.as_deref(),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct MessageDeltaContent {
+    pub role: Option<MessageRole>,
+    #[serde(default)]
+    pub content: Vec<MessageDeltaPart>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MessageDeltaPart {
+    Text {
+        index: usize,
+        text: TextDelta,
+    },
+    ImageFile {
+        index: usize,
+        image_file: ImageFile,
+    },
+    ImageUrl {
+        index: usize,
+        image_url: ImageUrl,
+    },
+    Refusal {
+        index: usize,
+        refusal: Option<String>,
+    },
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TextDelta {
+    pub value: Option<String>,
+    #[serde(default)]
+    pub annotations: Vec<serde_json::Value>,
+}
+
+impl Client {
+    /// Creates a thread, optionally seeded with messages
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _endpoint is unavailable_
+    /// * _deserialization of JSON Thread data fails_
+    #[tokio::main]
+    pub async fn create_thread(&self, params: ThreadParams) -> OpenAIResult<Thread> {
+        let url = format!("{}/threads", self.api_base);
+
+        self.send(self.beta_request(Method::POST, url).json(&params))
+            .await
+    }
+
+    #[tokio::main]
+    pub async fn retrieve_thread(&self, thread_id: &str) -> OpenAIResult<Thread> {
+        let url = format!("{}/threads/{}", self.api_base, thread_id);
+
+        self.send(self.beta_request(Method::GET, url)).await
+    }
+
+    #[tokio::main]
+    pub async fn modify_thread(
+        &self,
+        thread_id: &str,
+        params: ModifyThreadParams,
+    ) -> OpenAIResult<Thread> {
+        let url = format!("{}/threads/{}", self.api_base, thread_id);
+
+        self.send(self.beta_request(Method::POST, url).json(&params))
+            .await
+    }
+
+    #[tokio::main]
+    pub async fn delete_thread(&self, thread_id: &str) -> OpenAIResult<DeletionStatus> {
+        let url = format!("{}/threads/{}", self.api_base, thread_id);
+
+        self.send(self.beta_request(Method::DELETE, url)).await
+    }
+
+    /// Adds a message to the end of a thread
+    #[tokio::main]
+    pub async fn create_message(
+        &self,
+        thread_id: &str,
+        params: MessageParams,
+    ) -> OpenAIResult<ThreadMessage> {
+        let url = format!("{}/threads/{}/messages", self.api_base, thread_id);
+
+        self.send(self.beta_request(Method::POST, url).json(&params))
+            .await
+    }
+
+    #[tokio::main]
+    pub async fn retrieve_message(
+        &self,
+        thread_id: &str,
+        message_id: &str,
+    ) -> OpenAIResult<ThreadMessage> {
+        let url = format!(
+            "{}/threads/{}/messages/{}",
+            self.api_base, thread_id, message_id
+        );
+
+        self.send(self.beta_request(Method::GET, url)).await
+    }
+
+    #[tokio::main]
+    pub async fn modify_message(
+        &self,
+        thread_id: &str,
+        message_id: &str,
+        params: ModifyMessageParams,
+    ) -> OpenAIResult<ThreadMessage> {
+        let url = format!(
+            "{}/threads/{}/messages/{}",
+            self.api_base, thread_id, message_id
+        );
+
+        self.send(self.beta_request(Method::POST, url).json(&params))
+            .await
+    }
+
+    #[tokio::main]
+    pub async fn delete_message(
+        &self,
+        thread_id: &str,
+        message_id: &str,
+    ) -> OpenAIResult<DeletionStatus> {
+        let url = format!(
+            "{}/threads/{}/messages/{}",
+            self.api_base, thread_id, message_id
+        );
+
+        self.send(self.beta_request(Method::DELETE, url)).await
+    }
+
+    /// Returns one page of the messages on a thread, newest first unless an order is given
+    #[tokio::main]
+    pub async fn list_messages(
+        &self,
+        thread_id: &str,
+        params: ListParams,
+    ) -> OpenAIResult<Page<ThreadMessage>> {
+        let url = format!("{}/threads/{}/messages", self.api_base, thread_id);
+
+        self.send(self.beta_request(Method::GET, url).query(&params))
+            .await
+    }
+
+    /// Lazily walks every message on a thread, fetching further pages as needed
+    ///
+    /// The stream has to be polled inside a Tokio runtime
+    pub fn list_all_messages(
+        &self,
+        thread_id: &str,
+        params: ListParams,
+    ) -> impl Stream<Item = OpenAIResult<ThreadMessage>> + '_ {
+        let url = format!("{}/threads/{}/messages", self.api_base, thread_id);
+
+        self.paginate(
+            move |query| self.beta_request(Method::GET, url.clone()).query(query),
+            params,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_list_deserialization() {
+        let list_data = r#"
+        {
+            "object": "list",
+            "data": [
+                {
+                    "id": "msg_abc123",
+                    "object": "thread.message",
+                    "created_at": 1699016383,
+                    "assistant_id": "asst_abc123",
+                    "thread_id": "thread_abc123",
+                    "run_id": "run_abc123",
+                    "role": "assistant",
+                    "content": [
+                        { "type": "text", "text": { "value": "How does AI work? ", "annotations": [] } },
+                        { "type": "image_file", "image_file": { "file_id": "file-abc" } },
+                        { "type": "text", "text": { "value": "Explain it simply.", "annotations": [] } }
+                    ],
+                    "attachments": [],
+                    "metadata": {}
+                }
+            ],
+            "first_id": "msg_abc123",
+            "last_id": "msg_abc123",
+            "has_more": false
+        }"#;
+
+        let page: Page<ThreadMessage> = serde_json::from_str(list_data).unwrap();
+
+        assert!(!page.has_more);
+        assert_eq!(page.last_id, Some(String::from("msg_abc123")));
+        assert_eq!(page.data[0].role, MessageRole::Assistant);
+        assert_eq!(page.data[0].text(), "How does AI work? Explain it simply.");
+    }
+
+    #[test]
+    fn test_thread_params_serialization() {
+        let params = ThreadParams {
+            messages: vec![MessageParams::user(String::from("Hello"))],
+            ..Default::default()
+        };
+
+        let params_serialized = serde_json::to_string(&params).unwrap();
+
+        let params_json = r#"{"messages":[{"role":"user","content":"Hello"}]}"#;
+
+        assert_eq!(params_serialized, params_json);
+    }
+
+    #[test]
+    fn test_message_delta_text() {
+        let delta_data = r#"
+        {
+            "id": "msg_123",
+            "object": "thread.message.delta",
+            "delta": {
+                "content": [
+                    { "index": 0, "type": "text", "text": { "value": "Hello", "annotations": [] } }
+                ]
+            }
+        }"#;
+
+        let delta: MessageDelta = serde_json::from_str(delta_data).unwrap();
+
+        assert_eq!(delta.text(), "Hello");
+    }
+}
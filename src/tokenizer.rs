@@ -0,0 +1,937 @@
+use crate::error::{OpenAIError, OpenAIResult};
+use crate::param::{ChatParams, CompletionParams, Message, Prompt, Tool};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use fancy_regex::Regex;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+const ENDOFTEXT: &str = "<|endoftext|>";
+const FIM_PREFIX: &str = "<|fim_prefix|>";
+const FIM_MIDDLE: &str = "<|fim_middle|>";
+const FIM_SUFFIX: &str = "<|fim_suffix|>";
+const ENDOFPROMPT: &str = "<|endofprompt|>";
+
+/// Splits text the way GPT-2 and GPT-3 did before merging
+const R50K_PATTERN: &str =
+    r"'s|'t|'re|'ve|'m|'ll|'d| ?\p{L}+| ?\p{N}+| ?[^\s\p{L}\p{N}]+|\s+(?!\S)|\s+";
+
+const CL100K_PATTERN: &str = r"(?i:'s|'t|'re|'ve|'m|'ll|'d)|[^\r\n\p{L}\p{N}]?\p{L}+|\p{N}{1,3}| ?[^\s\p{L}\p{N}]+[\r\n]*|\s*[\r\n]+|\s+(?!\S)|\s+";
+
+const O200K_PATTERN: &str = concat!(
+    r"[^\r\n\p{L}\p{N}]?[\p{Lu}\p{Lt}\p{Lm}\p{Lo}\p{M}]*[\p{Ll}\p{Lm}\p{Lo}\p{M}]+(?i:'s|'t|'re|'ve|'m|'ll|'d)?",
+    r"|[^\r\n\p{L}\p{N}]?[\p{Lu}\p{Lt}\p{Lm}\p{Lo}\p{M}]+[\p{Ll}\p{Lm}\p{Lo}\p{M}]*(?i:'s|'t|'re|'ve|'m|'ll|'d)?",
+    r"|\p{N}{1,3}| ?[^\s\p{L}\p{N}]+[\r\n/]*|\s*[\r\n]+|\s+(?!\S)|\s+",
+);
+
+/// Model ids matched exactly, checked before `MODEL_PREFIXES`
+const MODELS: &[(&str, Encoding)] = &[
+    ("o1", Encoding::O200kBase),
+    ("o3", Encoding::O200kBase),
+    ("gpt-5", Encoding::O200kBase),
+    ("gpt-4.1", Encoding::O200kBase),
+    ("gpt-4o", Encoding::O200kBase),
+    ("chatgpt-4o-latest", Encoding::O200kBase),
+    ("gpt-4", Encoding::Cl100kBase),
+    ("gpt-3.5-turbo", Encoding::Cl100kBase),
+    ("gpt-35-turbo", Encoding::Cl100kBase),
+    ("davinci-002", Encoding::Cl100kBase),
+    ("babbage-002", Encoding::Cl100kBase),
+    ("text-embedding-ada-002", Encoding::Cl100kBase),
+    ("text-embedding-3-small", Encoding::Cl100kBase),
+    ("text-embedding-3-large", Encoding::Cl100kBase),
+    ("text-davinci-003", Encoding::P50kBase),
+    ("text-davinci-002", Encoding::P50kBase),
+    ("code-davinci-002", Encoding::P50kBase),
+    ("code-davinci-001", Encoding::P50kBase),
+    ("code-cushman-002", Encoding::P50kBase),
+    ("code-cushman-001", Encoding::P50kBase),
+    ("text-davinci-edit-001", Encoding::P50kEdit),
+    ("code-davinci-edit-001", Encoding::P50kEdit),
+    ("text-davinci-001", Encoding::R50kBase),
+    ("text-curie-001", Encoding::R50kBase),
+    ("text-babbage-001", Encoding::R50kBase),
+    ("text-ada-001", Encoding::R50kBase),
+    ("davinci", Encoding::R50kBase),
+    ("curie", Encoding::R50kBase),
+    ("babbage", Encoding::R50kBase),
+    ("ada", Encoding::R50kBase),
+    ("gpt2", Encoding::R50kBase),
+];
+
+/// Prefixes of dated snapshots and fine-tuned models, the longest matching one wins
+const MODEL_PREFIXES: &[(&str, Encoding)] = &[
+    ("o1-", Encoding::O200kBase),
+    ("o3-", Encoding::O200kBase),
+    ("o4-", Encoding::O200kBase),
+    ("gpt-5-", Encoding::O200kBase),
+    ("gpt-4.1-", Encoding::O200kBase),
+    ("gpt-4.5-", Encoding::O200kBase),
+    ("gpt-4o-", Encoding::O200kBase),
+    ("chatgpt-4o-", Encoding::O200kBase),
+    ("gpt-4-", Encoding::Cl100kBase),
+    ("gpt-3.5-turbo-", Encoding::Cl100kBase),
+    ("gpt-35-turbo-", Encoding::Cl100kBase),
+    ("ft:gpt-4o", Encoding::O200kBase),
+    ("ft:gpt-4.1", Encoding::O200kBase),
+    ("ft:gpt-4", Encoding::Cl100kBase),
+    ("ft:gpt-3.5-turbo", Encoding::Cl100kBase),
+    ("ft:davinci-002", Encoding::Cl100kBase),
+    ("ft:babbage-002", Encoding::Cl100kBase),
+];
+
+/// Limits of the models matched exactly, checked before `LIMIT_PREFIXES`
+const LIMITS: &[(&str, ModelLimits)] = &[
+    ("o1", ModelLimits::reasoning(200_000, 100_000)),
+    ("o1-mini", ModelLimits::reasoning(128_000, 65_536)),
+    ("o1-preview", ModelLimits::reasoning(128_000, 32_768)),
+    ("o3", ModelLimits::reasoning(200_000, 100_000)),
+    ("gpt-5", ModelLimits::reasoning(400_000, 128_000)),
+    ("gpt-4.1", ModelLimits::new(1_047_576, 32_768)),
+    ("gpt-4o", ModelLimits::new(128_000, 16_384)),
+    ("gpt-4o-2024-05-13", ModelLimits::new(128_000, 4_096)),
+    ("chatgpt-4o-latest", ModelLimits::new(128_000, 16_384)),
+    ("gpt-4", ModelLimits::new(8_192, 8_192)),
+    ("gpt-4-turbo", ModelLimits::new(128_000, 4_096)),
+    ("gpt-3.5-turbo", ModelLimits::new(16_385, 4_096)),
+    ("gpt-3.5-turbo-0301", ModelLimits::new(4_096, 4_096)),
+    ("gpt-3.5-turbo-0613", ModelLimits::new(4_096, 4_096)),
+    ("davinci-002", ModelLimits::new(16_384, 16_384)),
+    ("babbage-002", ModelLimits::new(16_384, 16_384)),
+    ("text-davinci-003", ModelLimits::new(4_097, 4_097)),
+    ("text-davinci-002", ModelLimits::new(4_097, 4_097)),
+    ("code-davinci-002", ModelLimits::new(8_001, 8_001)),
+    ("code-cushman-001", ModelLimits::new(2_048, 2_048)),
+    ("text-davinci-001", ModelLimits::new(2_049, 2_049)),
+    ("text-curie-001", ModelLimits::new(2_049, 2_049)),
+    ("text-babbage-001", ModelLimits::new(2_049, 2_049)),
+    ("text-ada-001", ModelLimits::new(2_049, 2_049)),
+    ("davinci", ModelLimits::new(2_049, 2_049)),
+    ("curie", ModelLimits::new(2_049, 2_049)),
+    ("babbage", ModelLimits::new(2_049, 2_049)),
+    ("ada", ModelLimits::new(2_049, 2_049)),
+];
+
+/// Limits of dated snapshots and fine-tuned models, the longest matching prefix wins
+const LIMIT_PREFIXES: &[(&str, ModelLimits)] = &[
+    ("o1-", ModelLimits::reasoning(200_000, 100_000)),
+    ("o1-mini-", ModelLimits::reasoning(128_000, 65_536)),
+    ("o1-preview-", ModelLimits::reasoning(128_000, 32_768)),
+    ("o3-", ModelLimits::reasoning(200_000, 100_000)),
+    ("o4-", ModelLimits::reasoning(200_000, 100_000)),
+    ("gpt-5-", ModelLimits::reasoning(400_000, 128_000)),
+    ("gpt-4.1-", ModelLimits::new(1_047_576, 32_768)),
+    ("gpt-4.5-", ModelLimits::new(128_000, 16_384)),
+    ("gpt-4o-", ModelLimits::new(128_000, 16_384)),
+    ("chatgpt-4o-", ModelLimits::new(128_000, 16_384)),
+    ("gpt-4-", ModelLimits::new(8_192, 8_192)),
+    ("gpt-4-32k", ModelLimits::new(32_768, 32_768)),
+    ("gpt-4-turbo-", ModelLimits::new(128_000, 4_096)),
+    ("gpt-4-0125-", ModelLimits::new(128_000, 4_096)),
+    ("gpt-4-1106-", ModelLimits::new(128_000, 4_096)),
+    ("gpt-3.5-turbo-", ModelLimits::new(16_385, 4_096)),
+    ("gpt-3.5-turbo-16k", ModelLimits::new(16_385, 16_385)),
+    ("gpt-3.5-turbo-instruct", ModelLimits::new(4_096, 4_096)),
+    ("ft:gpt-4o", ModelLimits::new(128_000, 16_384)),
+    ("ft:gpt-4.1", ModelLimits::new(1_047_576, 32_768)),
+    ("ft:gpt-4-", ModelLimits::new(8_192, 8_192)),
+    ("ft:gpt-3.5-turbo", ModelLimits::new(16_385, 4_096)),
+    ("ft:davinci-002", ModelLimits::new(16_384, 16_384)),
+    ("ft:babbage-002", ModelLimits::new(16_384, 16_384)),
+];
+
+/// Looks a model up by its exact id, then by the longest prefix matching it
+fn lookup<T: Copy>(exact: &[(&str, T)], prefixes: &[(&str, T)], model: &str) -> Option<T> {
+    if let Some((_, value)) = exact.iter().find(|(id, _)| *id == model) {
+        return Some(*value);
+    }
+
+    prefixes
+        .iter()
+        .filter(|(prefix, _)| model.starts_with(prefix))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, value)| *value)
+}
+
+/// The byte pair encodings used by OpenAI models
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    /// GPT-3 models like `davinci`, also known as `gpt2`
+    R50kBase,
+    /// Codex models, `text-davinci-002` and `text-davinci-003`
+    P50kBase,
+    /// `p50k_base` with the fill-in-the-middle tokens of the edit models
+    P50kEdit,
+    /// GPT-4, GPT-3.5 Turbo and the embedding models
+    Cl100kBase,
+    /// GPT-4o and the o-series reasoning models
+    O200kBase,
+}
+
+impl Encoding {
+    /// The encoding used by a model, given its id as in `CompletionParams::model`
+    pub fn for_model(model: &str) -> Option<Encoding> {
+        lookup(MODELS, MODEL_PREFIXES, model)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Encoding::R50kBase => "r50k_base",
+            Encoding::P50kBase => "p50k_base",
+            Encoding::P50kEdit => "p50k_edit",
+            Encoding::Cl100kBase => "cl100k_base",
+            Encoding::O200kBase => "o200k_base",
+        }
+    }
+
+    fn ranks(&self) -> &'static str {
+        match self {
+            Encoding::R50kBase => include_str!("../assets/r50k_base.tiktoken"),
+            Encoding::P50kBase | Encoding::P50kEdit => {
+                include_str!("../assets/p50k_base.tiktoken")
+            }
+            Encoding::Cl100kBase => include_str!("../assets/cl100k_base.tiktoken"),
+            Encoding::O200kBase => include_str!("../assets/o200k_base.tiktoken"),
+        }
+    }
+
+    fn pattern(&self) -> &'static str {
+        match self {
+            Encoding::R50kBase | Encoding::P50kBase | Encoding::P50kEdit => R50K_PATTERN,
+            Encoding::Cl100kBase => CL100K_PATTERN,
+            Encoding::O200kBase => O200K_PATTERN,
+        }
+    }
+
+    fn special_tokens(&self) -> &'static [(&'static str, u32)] {
+        match self {
+            Encoding::R50kBase | Encoding::P50kBase => &[(ENDOFTEXT, 50256)],
+            Encoding::P50kEdit => &[
+                (ENDOFTEXT, 50256),
+                (FIM_PREFIX, 50281),
+                (FIM_MIDDLE, 50282),
+                (FIM_SUFFIX, 50283),
+            ],
+            Encoding::Cl100kBase => &[
+                (ENDOFTEXT, 100257),
+                (FIM_PREFIX, 100258),
+                (FIM_MIDDLE, 100259),
+                (FIM_SUFFIX, 100260),
+                (ENDOFPROMPT, 100276),
+            ],
+            Encoding::O200kBase => &[(ENDOFTEXT, 199999), (ENDOFPROMPT, 200018)],
+        }
+    }
+}
+
+/// How many tokens a model can handle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModelLimits {
+    /// The most tokens the prompt and the completion can take up together
+    pub context_window: usize,
+    /// The most tokens a single completion can take up
+    pub max_output_tokens: usize,
+    /// Whether the model reasons before answering. Reasoning models take `max_completion_tokens`
+    /// and reject `max_tokens`
+    pub reasoning: bool,
+}
+
+impl ModelLimits {
+    const fn new(context_window: usize, max_output_tokens: usize) -> Self {
+        ModelLimits {
+            context_window,
+            max_output_tokens,
+            reasoning: false,
+        }
+    }
+
+    const fn reasoning(context_window: usize, max_output_tokens: usize) -> Self {
+        ModelLimits {
+            context_window,
+            max_output_tokens,
+            reasoning: true,
+        }
+    }
+
+    /// The limits of a model, given its id as in `CompletionParams::model`
+    pub fn for_model(model: &str) -> Option<ModelLimits> {
+        lookup(LIMITS, LIMIT_PREFIXES, model)
+    }
+
+    /// The `max_tokens` that fits the completion next to a prompt of `prompt_tokens`
+    ///
+    /// A requested `max_tokens` is lowered if it doesn't fit, and left alone otherwise. When none
+    /// is requested, the room left for the completion is filled
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _the prompt alone takes up the whole context window_
+    pub fn fit_max_tokens(
+        &self,
+        prompt_tokens: usize,
+        max_tokens: Option<usize>,
+    ) -> OpenAIResult<usize> {
+        let available = self.context_window.saturating_sub(prompt_tokens);
+        if available == 0 {
+            return Err(OpenAIError::ContextLengthExceeded {
+                prompt_tokens,
+                context_window: self.context_window,
+            });
+        }
+        let fitted = available.min(self.max_output_tokens);
+
+        Ok(max_tokens.map_or(fitted, |max_tokens| max_tokens.min(fitted)))
+    }
+}
+
+/// Encodes text into tokens and back, offline
+///
+/// ```
+/// use openai_rust::tokenizer::Tokenizer;
+///
+/// let tokenizer = Tokenizer::for_model("gpt-4o-mini").unwrap();
+/// let tokens = tokenizer.encode("hello world");
+///
+/// assert_eq!(tokens.len(), tokenizer.count("hello world"));
+/// assert_eq!(tokenizer.decode(&tokens).unwrap(), "hello world");
+/// ```
+#[derive(Debug)]
+pub struct Tokenizer {
+    encoding: Encoding,
+    encoder: HashMap<Vec<u8>, u32>,
+    decoder: HashMap<u32, Vec<u8>>,
+    special_encoder: HashMap<&'static str, u32>,
+    special_decoder: HashMap<u32, &'static str>,
+    regex: Regex,
+    special_regex: Regex,
+}
+
+impl Tokenizer {
+    /// The tokenizer of an encoding
+    ///
+    /// Each encoding is loaded from its bundled rank file on first use, and then shared
+    pub fn get(encoding: Encoding) -> &'static Tokenizer {
+        static TOKENIZERS: [OnceLock<Tokenizer>; 5] = [
+            OnceLock::new(),
+            OnceLock::new(),
+            OnceLock::new(),
+            OnceLock::new(),
+            OnceLock::new(),
+        ];
+
+        TOKENIZERS[encoding as usize].get_or_init(|| Tokenizer::load(encoding))
+    }
+
+    /// The tokenizer of the encoding used by a model
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _the model isn't known to this crate_
+    pub fn for_model(model: &str) -> OpenAIResult<&'static Tokenizer> {
+        Encoding::for_model(model)
+            .map(Tokenizer::get)
+            .ok_or_else(|| OpenAIError::UnknownModel(String::from(model)))
+    }
+
+    fn load(encoding: Encoding) -> Tokenizer {
+        let encoder: HashMap<Vec<u8>, u32> = encoding
+            .ranks()
+            .lines()
+            .filter_map(|line| {
+                let (token, rank) = line.split_once(' ')?;
+                Some((STANDARD.decode(token).ok()?, rank.parse().ok()?))
+            })
+            .collect();
+        let decoder = encoder
+            .iter()
+            .map(|(token, rank)| (*rank, token.clone()))
+            .collect();
+
+        let special_encoder: HashMap<&'static str, u32> =
+            encoding.special_tokens().iter().copied().collect();
+        let special_decoder = special_encoder
+            .iter()
+            .map(|(token, rank)| (*rank, *token))
+            .collect();
+        let special_pattern = special_encoder
+            .keys()
+            .map(|token| fancy_regex::escape(token))
+            .collect::<Vec<_>>()
+            .join("|");
+
+        Tokenizer {
+            encoding,
+            encoder,
+            decoder,
+            special_encoder,
+            special_decoder,
+            regex: Regex::new(encoding.pattern()).expect("bundled pattern is valid"),
+            special_regex: Regex::new(&special_pattern).expect("escaped tokens are valid"),
+        }
+    }
+
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Encodes text, treating special tokens such as `<|endoftext|>` as plain text
+    pub fn encode(&self, text: &str) -> Vec<u32> {
+        let mut tokens = Vec::new();
+        self.encode_ordinary_into(text, &mut tokens);
+        tokens
+    }
+
+    /// Encodes text, turning the special tokens it contains into their token ids
+    pub fn encode_with_special_tokens(&self, text: &str) -> Vec<u32> {
+        let mut tokens = Vec::new();
+        let mut start = 0;
+
+        for special in self.special_regex.find_iter(text).flatten() {
+            self.encode_ordinary_into(&text[start..special.start()], &mut tokens);
+            tokens.push(self.special_encoder[special.as_str()]);
+            start = special.end();
+        }
+        self.encode_ordinary_into(&text[start..], &mut tokens);
+
+        tokens
+    }
+
+    /// The number of tokens in the text, as counted by `encode`
+    pub fn count(&self, text: &str) -> usize {
+        self.encode(text).len()
+    }
+
+    /// Decodes tokens into text, replacing any invalid UTF-8 left by partial characters
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _a token isn't part of the encoding_
+    pub fn decode(&self, tokens: &[u32]) -> OpenAIResult<String> {
+        let bytes = self.decode_bytes(tokens)?;
+
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    /// Decodes tokens into the raw bytes they stand for
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _a token isn't part of the encoding_
+    pub fn decode_bytes(&self, tokens: &[u32]) -> OpenAIResult<Vec<u8>> {
+        let mut bytes = Vec::new();
+        for token in tokens {
+            match (self.decoder.get(token), self.special_decoder.get(token)) {
+                (Some(token_bytes), _) => bytes.extend_from_slice(token_bytes),
+                (None, Some(special)) => bytes.extend_from_slice(special.as_bytes()),
+                (None, None) => return Err(OpenAIError::InvalidToken(*token)),
+            }
+        }
+
+        Ok(bytes)
+    }
+
+    fn encode_ordinary_into(&self, text: &str, tokens: &mut Vec<u32>) {
+        for piece in self.regex.find_iter(text).flatten() {
+            let piece = piece.as_str().as_bytes();
+            match self.encoder.get(piece) {
+                Some(token) => tokens.push(*token),
+                None => tokens.extend(byte_pair_encode(piece, &self.encoder)),
+            }
+        }
+    }
+}
+
+/// Merges the lowest ranked pair of adjacent parts until no pair is part of the encoding
+///
+/// Returns the start of each remaining part, followed by the end of the piece
+fn byte_pair_merge(piece: &[u8], ranks: &HashMap<Vec<u8>, u32>) -> Vec<usize> {
+    let rank_of = |parts: &[(usize, u32)], i: usize| {
+        parts
+            .get(i + 2)
+            .and_then(|end| ranks.get(&piece[parts[i].0..end.0]))
+            .copied()
+            .unwrap_or(u32::MAX)
+    };
+
+    // Each part holds its start and the rank of merging it with the next part
+    let mut parts: Vec<(usize, u32)> = (0..=piece.len()).map(|i| (i, u32::MAX)).collect();
+    for i in 0..parts.len() - 2 {
+        parts[i].1 = rank_of(&parts, i);
+    }
+
+    loop {
+        let lowest = parts
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, rank))| *rank != u32::MAX)
+            .min_by_key(|(_, (_, rank))| *rank)
+            .map(|(i, _)| i);
+        let Some(i) = lowest else {
+            break;
+        };
+
+        parts.remove(i + 1);
+        parts[i].1 = rank_of(&parts, i);
+        if i > 0 {
+            parts[i - 1].1 = rank_of(&parts, i - 1);
+        }
+    }
+
+    parts.into_iter().map(|(start, _)| start).collect()
+}
+
+fn byte_pair_encode(piece: &[u8], ranks: &HashMap<Vec<u8>, u32>) -> Vec<u32> {
+    byte_pair_merge(piece, ranks)
+        .windows(2)
+        .map(|bounds| ranks[&piece[bounds[0]..bounds[1]]])
+        .collect()
+}
+
+/// Every reply is primed with `<|start|>assistant<|message|>`
+const REPLY_PRIMING: isize = 3;
+
+/// Tokens taken by the way tool definitions are laid out in the prompt
+const PROPERTIES_INIT: isize = 3;
+const PROPERTY_KEY: isize = 3;
+const ENUM_INIT: isize = -3;
+const ENUM_ITEM: isize = 3;
+const FUNCTIONS_END: isize = 12;
+
+/// The tokens wrapped around every function definition, more with the gpt-4 and gpt-3.5-turbo
+/// layout than with the gpt-4o one
+fn function_overhead(encoding: Encoding) -> isize {
+    match encoding {
+        Encoding::Cl100kBase => 10,
+        _ => 7,
+    }
+}
+
+/// The tokens wrapped around every message, and added by a message `name`
+///
+/// `gpt-3.5-turbo-0301` leaves out the role when a name is given
+fn message_overhead(model: &str) -> (isize, isize) {
+    match model {
+        "gpt-3.5-turbo-0301" => (4, -1),
+        _ => (3, 1),
+    }
+}
+
+/// Counts the tokens a chat takes up in the prompt, matching `Usage::prompt_tokens`
+///
+/// Only text is counted, so images, audio and files attached as content parts are left out.
+/// Tool calls made by assistant messages are counted from their name and arguments, which is an
+/// estimate
+///
+/// # Errors
+///
+/// This function will return an error if -
+/// * _the model isn't known to this crate_
+pub fn count_chat_tokens(messages: &[Message], model: &str) -> OpenAIResult<usize> {
+    let tokenizer = Tokenizer::for_model(model)?;
+    let (per_message, per_name) = message_overhead(model);
+
+    let mut count = REPLY_PRIMING;
+    for message in messages {
+        count += per_message;
+        count += tokenizer.count(message.role.as_str()) as isize;
+        count += tokenizer.count(&message.text()) as isize;
+        if let Some(name) = &message.name {
+            count += per_name + tokenizer.count(name) as isize;
+        }
+        for tool_call in message.tool_calls() {
+            count += tokenizer.count(&tool_call.function.name) as isize;
+            count += tokenizer.count(&tool_call.function.arguments) as isize;
+        }
+    }
+
+    Ok(count.max(0) as usize)
+}
+
+/// Counts the tokens that the definitions of the tools add to the prompt
+///
+/// Only the top-level properties of the parameters are counted, so nested objects and arrays
+/// are left out and make this an underestimate
+///
+/// # Errors
+///
+/// This function will return an error if -
+/// * _the model isn't known to this crate_
+pub fn count_tool_tokens(tools: &[Tool], model: &str) -> OpenAIResult<usize> {
+    let tokenizer = Tokenizer::for_model(model)?;
+    if tools.is_empty() {
+        return Ok(0);
+    }
+    let function_init = function_overhead(tokenizer.encoding);
+
+    let mut count = FUNCTIONS_END;
+    for Tool::Function { function } in tools {
+        let description = function.description.as_deref().unwrap_or_default();
+        count += function_init;
+        count += tokenizer.count(&format!(
+            "{}:{}",
+            function.name,
+            description.strip_suffix('.').unwrap_or(description)
+        )) as isize;
+
+        let properties = function
+            .parameters
+            .as_ref()
+            .and_then(|parameters| parameters.get("properties"))
+            .and_then(Value::as_object);
+        let Some(properties) = properties.filter(|properties| !properties.is_empty()) else {
+            continue;
+        };
+
+        count += PROPERTIES_INIT;
+        for (name, property) in properties {
+            count += PROPERTY_KEY;
+            if let Some(items) = property.get("enum").and_then(Value::as_array) {
+                count += ENUM_INIT;
+                for item in items {
+                    count += ENUM_ITEM + tokenizer.count(&json_text(item)) as isize;
+                }
+            }
+
+            let kind = property.get("type").map(json_text).unwrap_or_default();
+            let description = property
+                .get("description")
+                .map(json_text)
+                .unwrap_or_default();
+            count += tokenizer.count(&format!(
+                "{}:{}:{}",
+                name,
+                kind,
+                description.strip_suffix('.').unwrap_or(&description)
+            )) as isize;
+        }
+    }
+
+    Ok(count.max(0) as usize)
+}
+
+/// A JSON value as it reads in the prompt, without the quotes around strings
+fn json_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    }
+}
+
+fn model_limits(model: &str) -> OpenAIResult<ModelLimits> {
+    ModelLimits::for_model(model).ok_or_else(|| OpenAIError::UnknownModel(String::from(model)))
+}
+
+impl CompletionParams {
+    /// Counts the prompt tokens of the request, from the longest prompt and the suffix
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _the model isn't known to this crate_
+    pub fn count_prompt_tokens(&self) -> OpenAIResult<usize> {
+        let tokenizer = Tokenizer::for_model(&self.model)?;
+        let prompt = match &self.prompt {
+            Prompt::Text(text) => tokenizer.count(text),
+            Prompt::TextBatch(texts) => texts
+                .iter()
+                .map(|text| tokenizer.count(text))
+                .max()
+                .unwrap_or(0),
+            Prompt::Tokens(tokens) => tokens.len(),
+            Prompt::TokenBatch(batch) => batch.iter().map(Vec::len).max().unwrap_or(0),
+        };
+        let suffix = self
+            .opts
+            .suffix
+            .as_deref()
+            .map_or(0, |suffix| tokenizer.count(suffix));
+
+        Ok(prompt + suffix)
+    }
+
+    /// Sets `max_tokens` so that the prompt and the completion fit the context window of the
+    /// model, see `ModelLimits::fit_max_tokens`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _the model isn't known to this crate_
+    /// * _the prompt alone doesn't fit the context window_
+    pub fn fit_max_tokens(&mut self) -> OpenAIResult<()> {
+        let limits = model_limits(&self.model)?;
+        let max_tokens =
+            limits.fit_max_tokens(self.count_prompt_tokens()?, self.opts.max_tokens)?;
+        self.opts.max_tokens = Some(max_tokens);
+
+        Ok(())
+    }
+}
+
+impl ChatParams {
+    /// Counts the prompt tokens of the request, from its messages and tool definitions
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _the model isn't known to this crate_
+    pub fn count_prompt_tokens(&self) -> OpenAIResult<usize> {
+        Ok(count_chat_tokens(&self.messages, &self.model)?
+            + count_tool_tokens(&self.opt.tools, &self.model)?)
+    }
+
+    /// Sets `max_tokens` so that the prompt and the completion fit the context window of the
+    /// model, see `ModelLimits::fit_max_tokens`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _the model isn't known to this crate_
+    /// * _the prompt alone doesn't fit the context window_
+    ///
+    /// Reasoning models, and requests that already set `max_completion_tokens`, get the fitted
+    /// value in `max_completion_tokens` instead, with `max_tokens` cleared
+    pub fn fit_max_tokens(&mut self) -> OpenAIResult<()> {
+        let limits = model_limits(&self.model)?;
+        let prompt_tokens = self.count_prompt_tokens()?;
+
+        if limits.reasoning || self.opt.max_completion_tokens.is_some() {
+            let requested = match self.opt.max_completion_tokens {
+                Some(max_completion_tokens) => Some(max_completion_tokens),
+                None => self.opt.max_tokens,
+            };
+            let fitted = limits.fit_max_tokens(prompt_tokens, requested.map(|n| n as usize))?;
+            self.opt.max_completion_tokens = Some(fitted as u64);
+            self.opt.max_tokens = None;
+        } else {
+            let requested = self.opt.max_tokens.map(|max_tokens| max_tokens as usize);
+            let fitted = limits.fit_max_tokens(prompt_tokens, requested)?;
+            self.opt.max_tokens = Some(fitted as u64);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encoding_for_model() {
+        assert_eq!(Encoding::for_model("gpt-4o"), Some(Encoding::O200kBase));
+        assert_eq!(
+            Encoding::for_model("gpt-4o-mini-2024-07-18"),
+            Some(Encoding::O200kBase)
+        );
+        assert_eq!(
+            Encoding::for_model("gpt-4-0613"),
+            Some(Encoding::Cl100kBase)
+        );
+        assert_eq!(
+            Encoding::for_model("ft:gpt-3.5-turbo:acme::abc123"),
+            Some(Encoding::Cl100kBase)
+        );
+        assert_eq!(
+            Encoding::for_model("text-davinci-003"),
+            Some(Encoding::P50kBase)
+        );
+        assert_eq!(Encoding::for_model("davinci"), Some(Encoding::R50kBase));
+        assert_eq!(Encoding::for_model("unknown-model"), None);
+
+        assert_eq!(Encoding::for_model("gpt-5"), Some(Encoding::O200kBase));
+        assert_eq!(
+            Encoding::for_model("gpt-5-mini-2025-08-07"),
+            Some(Encoding::O200kBase)
+        );
+        assert_eq!(Encoding::for_model("gpt-50"), None);
+        assert!(ModelLimits::for_model("gpt-5").unwrap().reasoning);
+        assert!(ModelLimits::for_model("gpt-5-nano").unwrap().reasoning);
+        assert_eq!(ModelLimits::for_model("gpt-5o"), None);
+    }
+
+    #[test]
+    fn test_encode_decode_round_trip() {
+        let tokenizer = Tokenizer::get(Encoding::Cl100kBase);
+
+        assert_eq!(tokenizer.encode("hello world"), vec![15339, 1917]);
+        assert_eq!(
+            tokenizer.encode_with_special_tokens("hi<|endoftext|>"),
+            vec![6151, 100257]
+        );
+
+        let text = "Tokenizers split text like «antidisestablishmentarianism» into pieces 🦀";
+        let tokens = tokenizer.encode(text);
+        assert_eq!(tokenizer.decode(&tokens).unwrap(), text);
+        assert!(matches!(
+            tokenizer.decode(&[u32::MAX]),
+            Err(OpenAIError::InvalidToken(u32::MAX))
+        ));
+    }
+
+    #[test]
+    fn test_encodings_differ() {
+        assert_eq!(
+            Tokenizer::get(Encoding::O200kBase).encode("hello world"),
+            vec![24912, 2375]
+        );
+        assert_eq!(
+            Tokenizer::get(Encoding::R50kBase).encode("hello world"),
+            vec![31373, 995]
+        );
+    }
+
+    #[test]
+    fn test_fit_max_tokens() {
+        let limits = ModelLimits::for_model("gpt-4o-mini-2024-07-18").unwrap();
+        assert_eq!(limits, ModelLimits::new(128_000, 16_384));
+        assert_eq!(
+            ModelLimits::for_model("gpt-4-turbo-2024-04-09"),
+            Some(ModelLimits::new(128_000, 4_096))
+        );
+        assert_eq!(ModelLimits::for_model("unknown-model"), None);
+
+        assert_eq!(limits.fit_max_tokens(1_000, None).unwrap(), 16_384);
+        assert_eq!(limits.fit_max_tokens(1_000, Some(256)).unwrap(), 256);
+        assert_eq!(limits.fit_max_tokens(127_000, Some(4_096)).unwrap(), 1_000);
+        assert!(matches!(
+            limits.fit_max_tokens(128_000, None),
+            Err(OpenAIError::ContextLengthExceeded {
+                prompt_tokens: 128_000,
+                context_window: 128_000
+            })
+        ));
+
+        let mut completion_params = CompletionParams::builder("text-davinci-003")
+            .prompt(vec![vec![0; 4_000], vec![0; 10]])
+            .max_tokens(500)
+            .build()
+            .unwrap();
+        completion_params.fit_max_tokens().unwrap();
+        assert_eq!(completion_params.opts.max_tokens, Some(97));
+    }
+
+    #[test]
+    fn test_fit_max_completion_tokens_for_reasoning_models() {
+        let mut chat_params = ChatParams::builder("o3")
+            .user_message("Prove that there are infinitely many primes.")
+            .max_tokens(200_000)
+            .build()
+            .unwrap();
+        chat_params.fit_max_tokens().unwrap();
+
+        let body = serde_json::to_value(&chat_params).unwrap();
+        assert_eq!(body["max_completion_tokens"], 100_000);
+        assert!(body.get("max_tokens").is_none());
+
+        let mut chat_params = ChatParams::builder("gpt-5-mini")
+            .user_message("Hello")
+            .build()
+            .unwrap();
+        chat_params.fit_max_tokens().unwrap();
+
+        let body = serde_json::to_value(&chat_params).unwrap();
+        assert_eq!(body["max_completion_tokens"], 128_000);
+        assert!(body.get("max_tokens").is_none());
+
+        let mut chat_params = ChatParams::builder("gpt-4o")
+            .user_message("Hello")
+            .build()
+            .unwrap();
+        chat_params.fit_max_tokens().unwrap();
+
+        let body = serde_json::to_value(&chat_params).unwrap();
+        assert_eq!(body["max_tokens"], 16_384);
+        assert!(body.get("max_completion_tokens").is_none());
+    }
+
+    fn jargon_messages() -> Vec<Message> {
+        vec![
+            Message::system(String::from(
+                "You are a helpful, pattern-following assistant that translates corporate jargon into plain English.",
+            )),
+            Message::system(String::from("New synergies will help drive top-line growth."))
+                .with_name(String::from("example_user")),
+            Message::system(String::from(
+                "Things working well together will increase revenue.",
+            ))
+            .with_name(String::from("example_assistant")),
+            Message::system(String::from(
+                "Let's circle back when we have more bandwidth to touch base on opportunities for increased leverage.",
+            ))
+            .with_name(String::from("example_user")),
+            Message::system(String::from(
+                "Let's talk later when we're less busy about how to do better.",
+            ))
+            .with_name(String::from("example_assistant")),
+            Message::user(String::from(
+                "This late pivot means we don't have time to boil the ocean for the client deliverable.",
+            )),
+        ]
+    }
+
+    #[test]
+    fn test_count_chat_tokens() {
+        let messages = jargon_messages();
+
+        assert_eq!(
+            count_chat_tokens(&messages, "gpt-3.5-turbo-0301").unwrap(),
+            127
+        );
+        assert_eq!(count_chat_tokens(&messages, "gpt-4-0314").unwrap(), 129);
+        assert_eq!(
+            count_chat_tokens(&messages, "gpt-4o-2024-05-13").unwrap(),
+            124
+        );
+        assert!(matches!(
+            count_chat_tokens(&messages, "unknown-model"),
+            Err(OpenAIError::UnknownModel(_))
+        ));
+    }
+
+    #[test]
+    fn test_count_prompt_tokens_with_tools() {
+        let chat_params = ChatParams::builder(String::from("gpt-4o"))
+            .system_message(String::from(
+                "You are a helpful assistant that can answer to questions about the weather.",
+            ))
+            .user_message(String::from("What's the weather like in San Francisco?"))
+            .tool(Tool::function(
+                String::from("get_current_weather"),
+                String::from("Get the current weather in a given location"),
+                serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "location": {
+                            "type": "string",
+                            "description": "The city and state, e.g. San Francisco, CA",
+                        },
+                        "unit": {
+                            "type": "string",
+                            "description": "The unit of temperature to return",
+                            "enum": ["celsius", "fahrenheit"],
+                        },
+                    },
+                    "required": ["location"],
+                }),
+            ))
+            .build()
+            .unwrap();
+
+        assert_eq!(chat_params.count_prompt_tokens().unwrap(), 101);
+
+        let gpt_4 = ChatParams {
+            model: String::from("gpt-4"),
+            ..chat_params.clone()
+        };
+        assert_eq!(gpt_4.count_prompt_tokens().unwrap(), 105);
+        let gpt_35_turbo = ChatParams {
+            model: String::from("gpt-3.5-turbo"),
+            ..chat_params
+        };
+        assert_eq!(gpt_35_turbo.count_prompt_tokens().unwrap(), 105);
+    }
+}
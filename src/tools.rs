@@ -0,0 +1,306 @@
+use crate::construct::ToolCall;
+use crate::error::OpenAIResult;
+use crate::param::{FunctionDefinition, JsonSchemaFormat, ResponseFormat, Tool};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// A function tool described by a Rust type
+///
+/// The parameter schema comes from the `JsonSchema` derive, and the description from the doc
+/// comment of the type. The function name defaults to the type name in snake case
+///
+/// ```
+/// use schemars::JsonSchema;
+/// use serde::Deserialize;
+///
+/// /// Gets the current weather for a city
+/// #[derive(Deserialize, JsonSchema)]
+/// struct GetWeather {
+///     /// Name of the city, e.g. Paris
+///     city: String,
+/// }
+///
+/// impl openai_rust::tools::OpenAITool for GetWeather {}
+/// ```
+pub trait OpenAITool: DeserializeOwned + JsonSchema {
+    fn name() -> String {
+        to_snake_case(&Self::schema_name())
+    }
+
+    fn description() -> Option<String> {
+        schema_description::<Self>()
+    }
+
+    /// Whether the model is held to the schema exactly. Every field is then required, so
+    /// optional values have to be `Option`s
+    fn strict() -> bool {
+        true
+    }
+
+    fn definition() -> FunctionDefinition {
+        FunctionDefinition {
+            name: Self::name(),
+            description: Self::description(),
+            parameters: Some(json_schema::<Self>(Self::strict())),
+            strict: Some(Self::strict()),
+        }
+    }
+
+    /// The tool to add to `OptChatParams::tools`
+    fn tool() -> Tool {
+        Tool::Function {
+            function: Self::definition(),
+        }
+    }
+
+    /// Parses the `arguments` string generated by the model
+    fn from_arguments(arguments: &str) -> OpenAIResult<Self> {
+        Ok(serde_json::from_str(arguments)?)
+    }
+
+    /// Parses the arguments of a tool call, if the call is meant for this tool
+    fn from_tool_call(tool_call: &ToolCall) -> Option<OpenAIResult<Self>> {
+        if tool_call.function.name == Self::name() {
+            Some(Self::from_arguments(&tool_call.function.arguments))
+        } else {
+            None
+        }
+    }
+}
+
+/// A strict `json_schema` response format for replies deserialized into `T`
+pub fn response_format<T: JsonSchema>() -> ResponseFormat {
+    let name = T::schema_name()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+
+    ResponseFormat::JsonSchema {
+        json_schema: JsonSchemaFormat {
+            name,
+            description: schema_description::<T>(),
+            schema: Some(json_schema::<T>(true)),
+            strict: Some(true),
+        },
+    }
+}
+
+/// Generates the JSON Schema of `T` in the subset understood by the API
+///
+/// The `$schema` and `title` keywords and integer formats are dropped. In strict mode every
+/// object additionally lists all its properties as required and forbids any other
+pub(crate) fn json_schema<T: JsonSchema>(strict: bool) -> Value {
+    let mut schema: Value = schemars::schema_for!(T).into();
+
+    if let Value::Object(root) = &mut schema {
+        root.remove("$schema");
+        root.remove("title");
+        root.remove("description");
+    }
+    adapt_schema(&mut schema, strict);
+
+    schema
+}
+
+/// The doc comment of `T`, if any
+fn schema_description<T: JsonSchema>() -> Option<String> {
+    let schema = schemars::schema_for!(T);
+
+    schema
+        .get("description")
+        .and_then(Value::as_str)
+        .map(String::from)
+}
+
+/// Drops the number formats the API rejects and, in strict mode, makes every property of the
+/// object schemas required
+fn adapt_schema(schema: &mut Value, strict: bool) {
+    match schema {
+        Value::Object(object) => {
+            let kind = object.get("type").and_then(Value::as_str);
+            let is_object = kind == Some("object");
+            if matches!(kind, Some("integer" | "number")) {
+                object.remove("format");
+            }
+
+            if strict && is_object {
+                if let Some(Value::Object(properties)) = object.get("properties") {
+                    let required = properties.keys().cloned().map(Value::String).collect();
+                    object.insert(String::from("required"), Value::Array(required));
+                    object.insert(String::from("additionalProperties"), Value::Bool(false));
+                }
+            }
+
+            for (keyword, value) in object.iter_mut() {
+                match (keyword.as_str(), value) {
+                    // Maps of names to schemas, rather than schemas themselves
+                    ("properties" | "$defs" | "definitions", Value::Object(schemas)) => schemas
+                        .values_mut()
+                        .for_each(|schema| adapt_schema(schema, strict)),
+                    // Plain JSON values, which may happen to look like schemas
+                    ("enum" | "const" | "default" | "examples", _) => {}
+                    (_, value) => adapt_schema(value, strict),
+                }
+            }
+        }
+        Value::Array(values) => values
+            .iter_mut()
+            .for_each(|value| adapt_schema(value, strict)),
+        _ => {}
+    }
+}
+
+/// Splits words at a lower-to-upper boundary, and before the last capital of an acronym, so that
+/// `HTTPRequest` becomes `http_request`
+fn to_snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let previous = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            if previous.is_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_uppercase() && next_is_lower)
+            {
+                snake.push('_');
+            }
+        }
+        snake.extend(c.to_lowercase());
+    }
+    snake
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    /// Gets the current weather for a city
+    #[derive(Deserialize, JsonSchema, Debug, PartialEq)]
+    struct GetWeather {
+        /// Name of the city, e.g. Paris
+        city: String,
+        unit: Option<Unit>,
+        days: u32,
+    }
+
+    #[derive(Deserialize, JsonSchema, Debug, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum Unit {
+        Celsius,
+        Fahrenheit,
+    }
+
+    impl OpenAITool for GetWeather {}
+
+    #[test]
+    fn test_tool_definition_from_type() {
+        let definition = GetWeather::definition();
+
+        assert_eq!(definition.name, "get_weather");
+        assert_eq!(
+            definition.description.as_deref(),
+            Some("Gets the current weather for a city")
+        );
+        assert_eq!(definition.strict, Some(true));
+
+        let parameters = definition.parameters.unwrap();
+        assert_eq!(parameters["type"], "object");
+        assert_eq!(parameters["additionalProperties"], false);
+        assert_eq!(
+            parameters["required"],
+            serde_json::json!(["city", "days", "unit"])
+        );
+        assert_eq!(
+            parameters["properties"]["city"]["description"],
+            "Name of the city, e.g. Paris"
+        );
+        assert_eq!(parameters["properties"]["days"]["format"], Value::Null);
+        assert!(parameters.get("$schema").is_none());
+    }
+
+    #[test]
+    fn test_to_snake_case() {
+        assert_eq!(to_snake_case("GetWeather"), "get_weather");
+        assert_eq!(to_snake_case("HTTPRequest"), "http_request");
+        assert_eq!(to_snake_case("SendHTTPRequest"), "send_http_request");
+        assert_eq!(to_snake_case("ParseJSON"), "parse_json");
+        assert_eq!(to_snake_case("Get2FACode"), "get2_fa_code");
+        assert_eq!(to_snake_case("lookup"), "lookup");
+    }
+
+    #[test]
+    fn test_adapt_schema_only_touches_object_schemas() {
+        let mut schema = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "properties": {
+                    "type": "object",
+                    "properties": { "count": { "type": "integer", "format": "uint32" } }
+                },
+                "type": { "type": "string", "enum": [{ "properties": {} }] }
+            }
+        });
+        adapt_schema(&mut schema, true);
+
+        assert_eq!(
+            schema["required"],
+            serde_json::json!(["properties", "type"])
+        );
+        // The properties map itself isn't a schema
+        assert!(schema["properties"].get("required").is_none());
+        assert!(schema["properties"].get("additionalProperties").is_none());
+
+        let nested = &schema["properties"]["properties"];
+        assert_eq!(nested["required"], serde_json::json!(["count"]));
+        assert_eq!(nested["additionalProperties"], false);
+        assert!(nested["properties"]["count"].get("format").is_none());
+        assert_eq!(
+            schema["properties"]["type"]["enum"],
+            serde_json::json!([{ "properties": {} }])
+        );
+    }
+
+    #[test]
+    fn test_response_format_from_type() {
+        let format = response_format::<GetWeather>();
+
+        let ResponseFormat::JsonSchema { json_schema } = format else {
+            panic!("expected a json_schema response format");
+        };
+        assert_eq!(json_schema.name, "GetWeather");
+        assert_eq!(json_schema.strict, Some(true));
+        assert_eq!(
+            json_schema.schema.unwrap()["additionalProperties"],
+            Value::Bool(false)
+        );
+    }
+
+    #[test]
+    fn test_tool_call_arguments_parsing() {
+        let tool_call = ToolCall::function(
+            String::from("call_abc"),
+            String::from("get_weather"),
+            String::from(r#"{"city":"Paris","unit":"celsius","days":2}"#),
+        );
+
+        let args = GetWeather::from_tool_call(&tool_call).unwrap().unwrap();
+        assert_eq!(
+            args,
+            GetWeather {
+                city: String::from("Paris"),
+                unit: Some(Unit::Celsius),
+                days: 2,
+            }
+        );
+
+        let other_call = ToolCall::function(
+            String::from("call_def"),
+            String::from("get_time"),
+            String::from("{}"),
+        );
+        assert!(GetWeather::from_tool_call(&other_call).is_none());
+    }
+}
@@ -0,0 +1,684 @@
+use crate::construct::{DeletionStatus, Page};
+use crate::error::OpenAIResult;
+use crate::param::ListParams;
+use crate::Client;
+use chrono::serde::ts_seconds_option;
+use chrono::{DateTime, Utc};
+use futures::Stream;
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// VectorStore
+/// {
+/// "id": "vs_abc123",
+/// "object": "vector_store",
+/// "created_at": 1699061776,
+/// "name": "Support FAQ",
+/// "usage_bytes": 139920,
+/// "status": "completed",
+/// "file_counts": {...}
+/// }
+#[derive(Deserialize, Debug)]
+pub struct VectorStore {
+    pub id: String,
+    pub object: String,
+    #[serde(with = "ts_seconds_option")]
+    pub created_at: Option<DateTime<Utc>>,
+    pub name: Option<String>,
+    #[serde(default)]
+    pub usage_bytes: u64,
+    pub file_counts: FileCounts,
+    pub status: VectorStoreStatus,
+    pub expires_after: Option<ExpiresAfter>,
+    #[serde(default, with = "ts_seconds_option")]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(default, with = "ts_seconds_option")]
+    pub last_active_at: Option<DateTime<Utc>>,
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum VectorStoreStatus {
+    Expired,
+    InProgress,
+    Completed,
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+pub struct FileCounts {
+    pub in_progress: u32,
+    pub completed: u32,
+    pub failed: u32,
+    pub cancelled: u32,
+    pub total: u32,
+}
+
+/// Expiration policy of a vector store, counted from its last activity
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ExpiresAfter {
+    pub anchor: String,
+    pub days: u32,
+}
+
+impl ExpiresAfter {
+    pub fn days_after_last_active(days: u32) -> Self {
+        ExpiresAfter {
+            anchor: String::from("last_active_at"),
+            days,
+        }
+    }
+}
+
+/// How files are split into chunks before being embedded
+///
+/// `Auto` currently uses chunks of 800 tokens with an overlap of 400 tokens
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChunkingStrategy {
+    Auto,
+    Static {
+        #[serde(rename = "static")]
+        config: StaticChunking,
+    },
+    /// Reported for files that were chunked before chunking strategies were introduced
+    Other,
+}
+
+impl ChunkingStrategy {
+    /// Fixed size chunks. `max_chunk_size_tokens` must be between 100 and 4096, and the overlap
+    /// may not exceed half of it
+    pub fn fixed(max_chunk_size_tokens: u32, chunk_overlap_tokens: u32) -> Self {
+        ChunkingStrategy::Static {
+            config: StaticChunking {
+                max_chunk_size_tokens,
+                chunk_overlap_tokens,
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct StaticChunking {
+    pub max_chunk_size_tokens: u32,
+    pub chunk_overlap_tokens: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct VectorStoreParams {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub file_ids: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_after: Option<ExpiresAfter>,
+    /// Only applies when `file_ids` is not empty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunking_strategy: Option<ChunkingStrategy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ModifyVectorStoreParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_after: Option<ExpiresAfter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+/// Ingestion status of a file or a file batch
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum IngestionStatus {
+    InProgress,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+/// VectorStoreFile
+/// {
+/// "id": "file-abc123",
+/// "object": "vector_store.file",
+/// "created_at": 1699061776,
+/// "vector_store_id": "vs_abc123",
+/// "status": "completed",
+/// "usage_bytes": 1234
+/// }
+#[derive(Deserialize, Debug)]
+pub struct VectorStoreFile {
+    pub id: String,
+    pub object: String,
+    #[serde(with = "ts_seconds_option")]
+    pub created_at: Option<DateTime<Utc>>,
+    pub vector_store_id: String,
+    pub status: IngestionStatus,
+    #[serde(default)]
+    pub usage_bytes: u64,
+    pub last_error: Option<IngestionError>,
+    pub chunking_strategy: Option<ChunkingStrategy>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct IngestionError {
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VectorStoreFileParams {
+    /// Id of a file uploaded with the `assistants` purpose
+    pub file_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunking_strategy: Option<ChunkingStrategy>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct VectorStoreFileBatch {
+    pub id: String,
+    pub object: String,
+    #[serde(with = "ts_seconds_option")]
+    pub created_at: Option<DateTime<Utc>>,
+    pub vector_store_id: String,
+    pub status: IngestionStatus,
+    pub file_counts: FileCounts,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FileBatchParams {
+    pub file_ids: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunking_strategy: Option<ChunkingStrategy>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SearchParams {
+    pub query: String,
+    #[serde(flatten)]
+    pub opts: OptSearchParams,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct OptSearchParams {
+    /// Between 1 and 50, defaults to 10
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_num_results: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rewrite_query: Option<bool>,
+    /// Comparison or compound filter on the file attributes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filters: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ranking_options: Option<RankingOptions>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct RankingOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ranker: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score_threshold: Option<f32>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SearchResults {
+    pub object: String,
+    /// The queries that were actually run, which differ from ours when `rewrite_query` is set
+    #[serde(default)]
+    pub search_query: Vec<String>,
+    pub data: Vec<SearchResult>,
+    #[serde(default)]
+    pub has_more: bool,
+    pub next_page: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SearchResult {
+    pub file_id: String,
+    pub filename: String,
+    pub score: f32,
+    pub attributes: Option<HashMap<String, serde_json::Value>>,
+    pub content: Vec<SearchResultContent>,
+}
+
+impl SearchResult {
+    /// Joins the text chunks that matched the query
+    pub fn text(&self) -> String {
+        self.content
+            .iter()
+            .map(|chunk| chunk.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SearchResultContent {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub text: String,
+}
+
+impl Client {
+    fn vector_store_url(&self, vector_store_id: &str) -> String {
+        format!("{}/vector_stores/{}", self.api_base, vector_store_id)
+    }
+}
+
+impl Client {
+    /// Creates a vector store, optionally ingesting files into it straight away
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _one of the files doesn't exist_
+    /// * _endpoint is unavailable_
+    /// * _deserialization of JSON VectorStore data fails_
+    #[tokio::main]
+    pub async fn create_vector_store(
+        &self,
+        params: VectorStoreParams,
+    ) -> OpenAIResult<VectorStore> {
+        let url = format!("{}/vector_stores", self.api_base);
+
+        self.send(self.beta_request(Method::POST, url).json(&params))
+            .await
+    }
+
+    #[tokio::main]
+    pub async fn retrieve_vector_store(&self, vector_store_id: &str) -> OpenAIResult<VectorStore> {
+        self.send(self.beta_request(Method::GET, self.vector_store_url(vector_store_id)))
+            .await
+    }
+
+    #[tokio::main]
+    pub async fn modify_vector_store(
+        &self,
+        vector_store_id: &str,
+        params: ModifyVectorStoreParams,
+    ) -> OpenAIResult<VectorStore> {
+        self.send(
+            self.beta_request(Method::POST, self.vector_store_url(vector_store_id))
+                .json(&params),
+        )
+        .await
+    }
+
+    #[tokio::main]
+    pub async fn delete_vector_store(&self, vector_store_id: &str) -> OpenAIResult<DeletionStatus> {
+        self.send(self.beta_request(Method::DELETE, self.vector_store_url(vector_store_id)))
+            .await
+    }
+
+    #[tokio::main]
+    pub async fn list_vector_stores(&self, params: ListParams) -> OpenAIResult<Page<VectorStore>> {
+        let url = format!("{}/vector_stores", self.api_base);
+
+        self.send(self.beta_request(Method::GET, url).query(&params))
+            .await
+    }
+
+    /// Lazily walks every vector store, fetching further pages as needed
+    ///
+    /// The stream has to be polled inside a Tokio runtime
+    pub fn list_all_vector_stores(
+        &self,
+        params: ListParams,
+    ) -> impl Stream<Item = OpenAIResult<VectorStore>> + '_ {
+        let url = format!("{}/vector_stores", self.api_base);
+
+        self.paginate(
+            move |query| self.beta_request(Method::GET, url.clone()).query(query),
+            params,
+        )
+    }
+
+    /// Attaches a file to a vector store. Ingestion happens in the background, see
+    /// `poll_vector_store_file`
+    #[tokio::main]
+    pub async fn create_vector_store_file(
+        &self,
+        vector_store_id: &str,
+        params: VectorStoreFileParams,
+    ) -> OpenAIResult<VectorStoreFile> {
+        let url = format!("{}/files", self.vector_store_url(vector_store_id));
+
+        self.send(self.beta_request(Method::POST, url).json(&params))
+            .await
+    }
+
+    #[tokio::main]
+    pub async fn retrieve_vector_store_file(
+        &self,
+        vector_store_id: &str,
+        file_id: &str,
+    ) -> OpenAIResult<VectorStoreFile> {
+        let url = format!(
+            "{}/files/{}",
+            self.vector_store_url(vector_store_id),
+            file_id
+        );
+
+        self.send(self.beta_request(Method::GET, url)).await
+    }
+
+    /// Removes a file from a vector store. The file itself is not deleted
+    #[tokio::main]
+    pub async fn delete_vector_store_file(
+        &self,
+        vector_store_id: &str,
+        file_id: &str,
+    ) -> OpenAIResult<DeletionStatus> {
+        let url = format!(
+            "{}/files/{}",
+            self.vector_store_url(vector_store_id),
+            file_id
+        );
+
+        self.send(self.beta_request(Method::DELETE, url)).await
+    }
+
+    #[tokio::main]
+    pub async fn list_vector_store_files(
+        &self,
+        vector_store_id: &str,
+        params: ListParams,
+    ) -> OpenAIResult<Page<VectorStoreFile>> {
+        let url = format!("{}/files", self.vector_store_url(vector_store_id));
+
+        self.send(self.beta_request(Method::GET, url).query(&params))
+            .await
+    }
+
+    /// Lazily walks every file of a vector store, fetching further pages as needed
+    ///
+    /// The stream has to be polled inside a Tokio runtime
+    pub fn list_all_vector_store_files(
+        &self,
+        vector_store_id: &str,
+        params: ListParams,
+    ) -> impl Stream<Item = OpenAIResult<VectorStoreFile>> + '_ {
+        let url = format!("{}/files", self.vector_store_url(vector_store_id));
+
+        self.paginate(
+            move |query| self.beta_request(Method::GET, url.clone()).query(query),
+            params,
+        )
+    }
+
+    /// Blocks until the file is no longer `in_progress`, checking its status once every `interval`,
+    /// and fails with `OpenAIError::PollTimeout` once the poll timeout of the client passes
+    #[tokio::main]
+    pub async fn poll_vector_store_file(
+        &self,
+        vector_store_id: &str,
+        file_id: &str,
+        interval: Duration,
+    ) -> OpenAIResult<VectorStoreFile> {
+        let url = format!(
+            "{}/files/{}",
+            self.vector_store_url(vector_store_id),
+            file_id
+        );
+
+        self.before_poll_timeout(async {
+            loop {
+                let file: VectorStoreFile = self
+                    .send(self.beta_request(Method::GET, url.clone()))
+                    .await?;
+
+                if file.status != IngestionStatus::InProgress {
+                    return Ok(file);
+                }
+                tokio::time::sleep(interval).await;
+            }
+        })
+        .await
+    }
+
+    /// Attaches several files to a vector store at once
+    #[tokio::main]
+    pub async fn create_file_batch(
+        &self,
+        vector_store_id: &str,
+        params: FileBatchParams,
+    ) -> OpenAIResult<VectorStoreFileBatch> {
+        let url = format!("{}/file_batches", self.vector_store_url(vector_store_id));
+
+        self.send(self.beta_request(Method::POST, url).json(&params))
+            .await
+    }
+
+    #[tokio::main]
+    pub async fn retrieve_file_batch(
+        &self,
+        vector_store_id: &str,
+        batch_id: &str,
+    ) -> OpenAIResult<VectorStoreFileBatch> {
+        let url = format!(
+            "{}/file_batches/{}",
+            self.vector_store_url(vector_store_id),
+            batch_id
+        );
+
+        self.send(self.beta_request(Method::GET, url)).await
+    }
+
+    #[tokio::main]
+    pub async fn cancel_file_batch(
+        &self,
+        vector_store_id: &str,
+        batch_id: &str,
+    ) -> OpenAIResult<VectorStoreFileBatch> {
+        let url = format!(
+            "{}/file_batches/{}/cancel",
+            self.vector_store_url(vector_store_id),
+            batch_id
+        );
+
+        self.send(self.beta_request(Method::POST, url)).await
+    }
+
+    #[tokio::main]
+    pub async fn list_file_batch_files(
+        &self,
+        vector_store_id: &str,
+        batch_id: &str,
+        params: ListParams,
+    ) -> OpenAIResult<Page<VectorStoreFile>> {
+        let url = format!(
+            "{}/file_batches/{}/files",
+            self.vector_store_url(vector_store_id),
+            batch_id
+        );
+
+        self.send(self.beta_request(Method::GET, url).query(&params))
+            .await
+    }
+
+    /// Creates a file batch and blocks until every file in it has been processed
+    #[tokio::main]
+    pub async fn create_and_poll_file_batch(
+        &self,
+        vector_store_id: &str,
+        params: FileBatchParams,
+        interval: Duration,
+    ) -> OpenAIResult<VectorStoreFileBatch> {
+        let url = format!("{}/file_batches", self.vector_store_url(vector_store_id));
+        let batch: VectorStoreFileBatch = self
+            .send(self.beta_request(Method::POST, url).json(&params))
+            .await?;
+
+        self.wait_for_file_batch(vector_store_id, &batch.id, interval)
+            .await
+    }
+
+    /// Blocks until the batch is no longer `in_progress`, checking its status once every `interval`,
+    /// and fails with `OpenAIError::PollTimeout` once the poll timeout of the client passes
+    #[tokio::main]
+    pub async fn poll_file_batch(
+        &self,
+        vector_store_id: &str,
+        batch_id: &str,
+        interval: Duration,
+    ) -> OpenAIResult<VectorStoreFileBatch> {
+        self.wait_for_file_batch(vector_store_id, batch_id, interval)
+            .await
+    }
+
+    /// Searches a vector store for the chunks most relevant to a query
+    #[tokio::main]
+    pub async fn search_vector_store(
+        &self,
+        vector_store_id: &str,
+        params: SearchParams,
+    ) -> OpenAIResult<SearchResults> {
+        let url = format!("{}/search", self.vector_store_url(vector_store_id));
+
+        self.send(self.beta_request(Method::POST, url).json(&params))
+            .await
+    }
+
+    async fn wait_for_file_batch(
+        &self,
+        vector_store_id: &str,
+        batch_id: &str,
+        interval: Duration,
+    ) -> OpenAIResult<VectorStoreFileBatch> {
+        let url = format!(
+            "{}/file_batches/{}",
+            self.vector_store_url(vector_store_id),
+            batch_id
+        );
+
+        self.before_poll_timeout(async {
+            loop {
+                let batch: VectorStoreFileBatch = self
+                    .send(self.beta_request(Method::GET, url.clone()))
+                    .await?;
+
+                if batch.status != IngestionStatus::InProgress {
+                    return Ok(batch);
+                }
+                tokio::time::sleep(interval).await;
+            }
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_polling_gives_up_after_timeout() {
+        use crate::error::OpenAIError;
+
+        // The batch never leaves `in_progress`
+        let api_base = crate::stand_in::stand_in_server(|_| {
+            String::from(
+                r#"{"id":"vsfb_abc123","object":"vector_store.file_batch","created_at":1699061776,"vector_store_id":"vs_abc123","status":"in_progress","file_counts":{"in_progress":1,"completed":0,"failed":0,"cancelled":0,"total":1}}"#,
+            )
+        });
+
+        let client = Client::new(String::from("keystring"))
+            .with_api_base(api_base)
+            .with_poll_timeout(Duration::from_millis(300));
+        let result = client
+            .wait_for_file_batch("vs_abc123", "vsfb_abc123", Duration::from_millis(50))
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(OpenAIError::PollTimeout(timeout)) if timeout == Duration::from_millis(300)
+        ));
+    }
+
+    #[test]
+    fn test_vector_store_deserialization() {
+        let vector_store_data = r#"
+        {
+            "id": "vs_abc123",
+            "object": "vector_store",
+            "created_at": 1699061776,
+            "name": "Support FAQ",
+            "usage_bytes": 139920,
+            "file_counts": {
+                "in_progress": 0,
+                "completed": 3,
+                "failed": 0,
+                "cancelled": 0,
+                "total": 3
+            },
+            "status": "completed",
+            "expires_after": { "anchor": "last_active_at", "days": 7 },
+            "expires_at": null,
+            "last_active_at": 1699061776,
+            "metadata": {}
+        }"#;
+
+        let vector_store: VectorStore = serde_json::from_str(vector_store_data).unwrap();
+
+        assert_eq!(vector_store.status, VectorStoreStatus::Completed);
+        assert_eq!(vector_store.file_counts.completed, 3);
+        assert_eq!(
+            vector_store.expires_after,
+            Some(ExpiresAfter::days_after_last_active(7))
+        );
+    }
+
+    #[test]
+    fn test_chunking_strategy_serialization() {
+        let params = FileBatchParams {
+            file_ids: vec![String::from("file-abc"), String::from("file-def")],
+            chunking_strategy: Some(ChunkingStrategy::fixed(400, 200)),
+        };
+
+        let params_serialized = serde_json::to_string(&params).unwrap();
+
+        let params_json = r#"{"file_ids":["file-abc","file-def"],"chunking_strategy":{"type":"static","static":{"max_chunk_size_tokens":400,"chunk_overlap_tokens":200}}}"#;
+
+        assert_eq!(params_serialized, params_json);
+        assert_eq!(
+            serde_json::to_string(&ChunkingStrategy::Auto).unwrap(),
+            r#"{"type":"auto"}"#
+        );
+    }
+
+    #[test]
+    fn test_search_results_deserialization() {
+        let results_data = r#"
+        {
+            "object": "vector_store.search_results.page",
+            "search_query": ["return policy"],
+            "data": [
+                {
+                    "file_id": "file-abc",
+                    "filename": "faq.md",
+                    "score": 0.87,
+                    "attributes": { "team": "support" },
+                    "content": [
+                        { "type": "text", "text": "Items can be returned" },
+                        { "type": "text", "text": "within 30 days." }
+                    ]
+                }
+            ],
+            "has_more": false,
+            "next_page": null
+        }"#;
+
+        let results: SearchResults = serde_json::from_str(results_data).unwrap();
+
+        assert_eq!(results.search_query, vec![String::from("return policy")]);
+        assert_eq!(
+            results.data[0].text(),
+            "Items can be returned\nwithin 30 days."
+        );
+    }
+}
@@ -0,0 +1,209 @@
+use crate::error::{OpenAIError, OpenAIResult};
+use crate::param::{ContentPart, ImageDetail, ImageUrl};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::path::Path;
+
+/// How an image is attached to a message
+#[derive(Debug, Default, Clone)]
+pub struct ImageOptions {
+    pub detail: Option<ImageDetail>,
+    /// Shrinks the image to the size the model scales it down to anyway before sending it, which
+    /// saves upload time without changing what the model sees. URLs are never downscaled
+    ///
+    /// Requires the `image` feature, images fail with `OpenAIError::ImageError` without it
+    pub downscale: bool,
+}
+
+impl ContentPart {
+    /// An image part for a remote URL
+    pub fn image_from_url(url: String, options: &ImageOptions) -> Self {
+        ContentPart::ImageUrl {
+            image_url: ImageUrl {
+                url,
+                detail: options.detail,
+            },
+        }
+    }
+
+    /// An image part carrying the given bytes as a base64 `data:` URL
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _downscaling is enabled and the image can't be decoded_
+    /// * _downscaling is enabled without the `image` feature_
+    pub fn image_from_bytes(
+        bytes: &[u8],
+        mime_type: &str,
+        options: &ImageOptions,
+    ) -> OpenAIResult<Self> {
+        if options.downscale {
+            let (bytes, mime_type) = downscale(bytes, mime_type, options.detail)?;
+            return Ok(Self::image_from_url(data_url(&bytes, &mime_type), options));
+        }
+
+        Ok(Self::image_from_url(data_url(bytes, mime_type), options))
+    }
+
+    /// An image part for a local file, with the MIME type guessed from its extension
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if -
+    /// * _the file can't be read_
+    /// * _the extension isn't one of png, jpg, jpeg, gif or webp_
+    /// * _downscaling is enabled and the image can't be decoded_
+    pub fn image_from_path<P: AsRef<Path>>(path: P, options: &ImageOptions) -> OpenAIResult<Self> {
+        let path = path.as_ref();
+        let mime_type = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(image_mime_type)
+            .ok_or_else(|| OpenAIError::UnsupportedImageType(path.display().to_string()))?;
+
+        Self::image_from_bytes(&std::fs::read(path)?, mime_type, options)
+    }
+}
+
+/// Encodes bytes as a `data:<mime type>;base64,...` URL
+pub fn data_url(bytes: &[u8], mime_type: &str) -> String {
+    format!("data:{};base64,{}", mime_type, STANDARD.encode(bytes))
+}
+
+/// The image types accepted by the vision models
+fn image_mime_type(extension: &str) -> Option<&'static str> {
+    match extension.to_ascii_lowercase().as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        _ => None,
+    }
+}
+
+/// The largest size an image is processed at, given as the bounds of its longest and shortest side
+///
+/// Low detail images are seen at 512x512. Otherwise they are fit within 2048x2048 and then
+/// scaled so that the shortest side is at most 768
+#[cfg(feature = "image")]
+fn size_limits(detail: Option<ImageDetail>) -> (u32, u32) {
+    match detail {
+        Some(ImageDetail::Low) => (512, 512),
+        _ => (2048, 768),
+    }
+}
+
+/// Scales the image down to the detail limits, keeping its format when it can be re-encoded
+///
+/// Images that already fit are returned untouched
+#[cfg(feature = "image")]
+fn downscale(
+    bytes: &[u8],
+    mime_type: &str,
+    detail: Option<ImageDetail>,
+) -> OpenAIResult<(Vec<u8>, String)> {
+    use image::{imageops::FilterType, ImageFormat, ImageReader};
+    use std::io::Cursor;
+
+    let reader = ImageReader::new(Cursor::new(bytes)).with_guessed_format()?;
+    let format = reader.format().unwrap_or(ImageFormat::Png);
+    let (width, height) = reader.into_dimensions()?;
+
+    let (max_long, max_short) = size_limits(detail);
+    let (long, short) = (width.max(height), width.min(height));
+    let scale = f64::min(
+        max_long as f64 / long as f64,
+        max_short as f64 / short as f64,
+    );
+    if scale >= 1.0 {
+        return Ok((bytes.to_vec(), String::from(mime_type)));
+    }
+
+    let image = image::load_from_memory_with_format(bytes, format)?;
+    let resized = image.resize(
+        (width as f64 * scale).round() as u32,
+        (height as f64 * scale).round() as u32,
+        FilterType::Lanczos3,
+    );
+    let format = match format {
+        ImageFormat::Jpeg | ImageFormat::Gif | ImageFormat::WebP => format,
+        _ => ImageFormat::Png,
+    };
+    let mut output = Cursor::new(Vec::new());
+    resized.write_to(&mut output, format)?;
+
+    Ok((output.into_inner(), String::from(format.to_mime_type())))
+}
+
+#[cfg(not(feature = "image"))]
+fn downscale(
+    _bytes: &[u8],
+    _mime_type: &str,
+    _detail: Option<ImageDetail>,
+) -> OpenAIResult<(Vec<u8>, String)> {
+    Err(OpenAIError::ImageError(String::from(
+        "downscaling requires the `image` feature",
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_from_bytes_data_url() {
+        let options = ImageOptions {
+            detail: Some(ImageDetail::High),
+            downscale: false,
+        };
+        let part = ContentPart::image_from_bytes(b"\x89PNG", "image/png", &options).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&part).unwrap(),
+            serde_json::json!({
+                "type": "image_url",
+                "image_url": {"url": "data:image/png;base64,iVBORw==", "detail": "high"}
+            })
+        );
+    }
+
+    #[test]
+    fn test_image_from_path_rejects_unknown_type() {
+        let result = ContentPart::image_from_path("notes.txt", &ImageOptions::default());
+
+        assert!(matches!(result, Err(OpenAIError::UnsupportedImageType(_))));
+    }
+
+    #[cfg(not(feature = "image"))]
+    #[test]
+    fn test_downscale_without_feature_fails() {
+        let options = ImageOptions {
+            detail: None,
+            downscale: true,
+        };
+        let result = ContentPart::image_from_bytes(b"\x89PNG", "image/png", &options);
+
+        assert!(matches!(result, Err(OpenAIError::ImageError(_))));
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_downscale_to_detail_limits() {
+        use std::io::Cursor;
+
+        let mut png = Cursor::new(Vec::new());
+        image::DynamicImage::new_rgb8(4096, 512)
+            .write_to(&mut png, image::ImageFormat::Png)
+            .unwrap();
+        let png = png.into_inner();
+
+        let (bytes, mime_type) = downscale(&png, "image/png", None).unwrap();
+        let resized = image::load_from_memory(&bytes).unwrap();
+        assert_eq!(mime_type, "image/png");
+        assert_eq!((resized.width(), resized.height()), (2048, 256));
+
+        let (bytes, _) = downscale(&png, "image/png", Some(ImageDetail::Low)).unwrap();
+        let resized = image::load_from_memory(&bytes).unwrap();
+        assert_eq!((resized.width(), resized.height()), (512, 64));
+    }
+}
@@ -1,5 +1,9 @@
-use crate::openai_rust;
+// Every integration test uses only some of these helpers
+#![allow(dead_code)]
+
 use dotenv::dotenv;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
 
 pub fn setup() -> openai_rust::Client {
     // This line loads the environment variables from the ".env" file.
@@ -9,3 +13,63 @@ pub fn setup() -> openai_rust::Client {
     let key = std::env::var("OPENAI_API_KEY").expect("OPENAI_API_KEY must be set");
     openai_rust::Client::new(key)
 }
+
+/// Answers every request with the JSON body that `respond` returns for it, until the test ends
+///
+/// `respond` receives the whole request, head and body. Returns the base URL to pass to
+/// `Client::with_api_base`
+pub fn stand_in_server<F>(mut respond: F) -> String
+where
+    F: FnMut(&str) -> String + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let api_base = format!("http://{}/v1", listener.local_addr().unwrap());
+
+    std::thread::spawn(move || {
+        for socket in listener.incoming() {
+            let mut socket = socket.unwrap();
+            let body = respond(&read_request(&mut socket));
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).unwrap();
+        }
+    });
+
+    api_base
+}
+
+/// The body of a request received by `stand_in_server`
+pub fn request_body(request: &str) -> &str {
+    request.split_once("\r\n\r\n").map_or("", |(_, body)| body)
+}
+
+/// Reads one request, up to the end of the body announced by its `content-length`
+fn read_request(socket: &mut TcpStream) -> String {
+    let mut request = Vec::new();
+    let mut buf = [0; 4096];
+    loop {
+        let n = socket.read(&mut buf).unwrap();
+        if n == 0 {
+            return String::from_utf8_lossy(&request).into_owned();
+        }
+        request.extend_from_slice(&buf[..n]);
+
+        let text = String::from_utf8_lossy(&request);
+        if let Some(end) = text.find("\r\n\r\n") {
+            let length = text[..end]
+                .lines()
+                .find_map(|line| {
+                    let line = line.to_ascii_lowercase();
+                    line.strip_prefix("content-length:")
+                        .map(|length| length.trim().parse::<usize>().unwrap())
+                })
+                .unwrap_or(0);
+            if request.len() >= end + 4 + length {
+                return text.into_owned();
+            }
+        }
+    }
+}
@@ -1,5 +1,3 @@
-use openai_rust;
-
 mod common;
 
 #[test]
@@ -53,10 +51,25 @@ fn test_create_completion() {
     let completion_params: openai_rust::param::CompletionParams =
         openai_rust::param::CompletionParams {
             model: String::from("text-davinci-003"),
-            prompt: String::from("Say this is a test"),
+            prompt: openai_rust::param::Prompt::from("Say this is a test"),
             opts: openai_rust::param::OptParams::default(),
         };
 
     let resp = client.create_completion(completion_params);
     assert!(resp.is_ok());
 }
+
+#[test]
+fn test_create_chat_completion() {
+    let client = common::setup();
+    let chat_params = openai_rust::param::ChatParams {
+        model: String::from("gpt-4o-mini"),
+        messages: vec![openai_rust::param::Message::user(String::from(
+            "Say this is a test",
+        ))],
+        opt: openai_rust::param::OptChatParams::default(),
+    };
+
+    let resp = client.create_chat_completion(chat_params);
+    assert!(resp.is_ok());
+}
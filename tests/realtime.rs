@@ -0,0 +1,81 @@
+use futures::{SinkExt, StreamExt};
+use openai_rust::realtime::{ClientEvent, ConversationItem, RealtimeSession, ServerEvent};
+use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+use tokio_tungstenite::tungstenite::Message;
+
+/// Accepts a single connection and plays the part of the Realtime API for one exchange
+// The handshake callback signature, and its large error type, is dictated by tungstenite
+#[allow(clippy::result_large_err)]
+async fn stand_in_server(listener: TcpListener) {
+    let (stream, _) = listener.accept().await.unwrap();
+    let ws = tokio_tungstenite::accept_hdr_async(stream, |req: &Request, resp: Response| {
+        assert_eq!(req.headers()["authorization"], "Bearer keystring");
+        assert_eq!(req.headers()["openai-beta"], "realtime=v1");
+        Ok(resp)
+    })
+    .await
+    .unwrap();
+    let (mut sink, mut stream) = ws.split();
+
+    sink.send(Message::Text(String::from(
+        r#"{"type":"session.created","event_id":"event_1","session":{"id":"sess_1"}}"#,
+    )))
+    .await
+    .unwrap();
+
+    let item = stream.next().await.unwrap().unwrap();
+    assert!(item
+        .to_text()
+        .unwrap()
+        .contains(r#""type":"conversation.item.create""#));
+    let create = stream.next().await.unwrap().unwrap();
+    assert_eq!(create.to_text().unwrap(), r#"{"type":"response.create"}"#);
+
+    for event in [
+        r#"{"type":"response.text.delta","event_id":"event_2","response_id":"resp_1","item_id":"item_1","output_index":0,"content_index":0,"delta":"Hello"}"#,
+        r#"{"type":"response.function_call_arguments.done","event_id":"event_3","response_id":"resp_1","item_id":"item_2","output_index":1,"call_id":"call_1","name":"get_weather","arguments":"{}"}"#,
+        r#"{"type":"error","event_id":"event_4","error":{"type":"server_error","code":null,"message":"Boom","param":null,"event_id":null}}"#,
+    ] {
+        sink.send(Message::Text(String::from(event))).await.unwrap();
+    }
+    sink.send(Message::Close(None)).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_realtime_session_against_stand_in() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let server = tokio::spawn(stand_in_server(listener));
+
+    let client = openai_rust::Client::new(String::from("keystring"));
+    let mut session = RealtimeSession::connect(&client, &url).await.unwrap();
+
+    let created = session.next().await.unwrap().unwrap();
+    assert!(matches!(created, ServerEvent::SessionCreated { .. }));
+
+    session
+        .send(ClientEvent::ConversationItemCreate {
+            item: ConversationItem::user_text(String::from("Hi")),
+        })
+        .await
+        .unwrap();
+    session
+        .send(ClientEvent::ResponseCreate { response: None })
+        .await
+        .unwrap();
+
+    let delta = session.next().await.unwrap().unwrap();
+    assert!(matches!(delta, ServerEvent::TextDelta { delta, .. } if delta == "Hello"));
+
+    let call = session.next().await.unwrap().unwrap();
+    assert!(
+        matches!(call, ServerEvent::FunctionCallArgumentsDone { call_id, name, .. } if call_id == "call_1" && name.as_deref() == Some("get_weather"))
+    );
+
+    let error = session.next().await.unwrap().unwrap();
+    assert!(matches!(error, ServerEvent::Error { error } if error.message == "Boom"));
+
+    assert!(session.next().await.is_none());
+    server.await.unwrap();
+}
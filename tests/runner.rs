@@ -0,0 +1,152 @@
+use openai_rust::error::OpenAIError;
+use openai_rust::param::{ChatParams, FunctionDefinition, Message, Tool};
+use openai_rust::runner::ToolRunner;
+use openai_rust::Client;
+use std::sync::mpsc::{self, Receiver};
+
+mod common;
+
+/// A chat completion whose only choice is `message`, stopped for `finish_reason`
+fn completion(message: &str, finish_reason: &str) -> String {
+    format!(
+        r#"{{"id":"chatcmpl-1","object":"chat.completion","created":1699896916,"model":"gpt-4o-mini","choices":[{{"index":0,"message":{},"logprobs":null,"finish_reason":"{}"}}]}}"#,
+        message, finish_reason
+    )
+}
+
+fn tool_call_reply(call_id: &str) -> String {
+    let message = format!(
+        r#"{{"role":"assistant","content":null,"tool_calls":[{{"id":"{}","type":"function","function":{{"name":"get_weather","arguments":"{{\"city\":\"Paris\"}}"}}}}]}}"#,
+        call_id
+    );
+
+    completion(&message, "tool_calls")
+}
+
+fn text_reply(text: &str, finish_reason: &str) -> String {
+    completion(
+        &format!(r#"{{"role":"assistant","content":"{}"}}"#, text),
+        finish_reason,
+    )
+}
+
+/// Answers the requests with `replies` in order, sending their bodies to the returned receiver
+fn scripted_server(replies: Vec<String>) -> (String, Receiver<String>) {
+    let (bodies, received) = mpsc::channel();
+    let mut replies = replies.into_iter();
+    let api_base = common::stand_in_server(move |request| {
+        bodies
+            .send(String::from(common::request_body(request)))
+            .unwrap();
+        replies.next().expect("no reply left for the request")
+    });
+
+    (api_base, received)
+}
+
+fn get_weather() -> FunctionDefinition {
+    FunctionDefinition {
+        name: String::from("get_weather"),
+        description: Some(String::from("Gets the weather of a city")),
+        parameters: None,
+        strict: None,
+    }
+}
+
+fn weather_runner() -> ToolRunner {
+    ToolRunner::new().register(get_weather(), |arguments| async move {
+        assert_eq!(arguments, r#"{"city":"Paris"}"#);
+        Ok::<_, String>(String::from("Sunny"))
+    })
+}
+
+fn chat_params() -> ChatParams {
+    ChatParams::builder("gpt-4o-mini")
+        .user_message("What's the weather in Paris?")
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_runner_answers_tool_calls_until_final_reply() {
+    let (api_base, bodies) = scripted_server(vec![
+        tool_call_reply("call_1"),
+        text_reply("It's sunny in Paris.", "stop"),
+    ]);
+    let client = Client::new(String::from("keystring")).with_api_base(api_base);
+
+    // A stale definition of a registered tool is replaced rather than sent twice
+    let mut chat_params = chat_params();
+    chat_params.opt.tools.push(Tool::Function {
+        function: FunctionDefinition {
+            description: Some(String::from("Outdated")),
+            ..get_weather()
+        },
+    });
+
+    let transcript = weather_runner().run(&client, chat_params).unwrap();
+
+    assert_eq!(transcript.len(), 4);
+    assert_eq!(transcript[0].text(), "What's the weather in Paris?");
+    assert_eq!(transcript[1].tool_calls()[0].id, "call_1");
+    assert_eq!(
+        transcript[2],
+        Message::tool(String::from("call_1"), String::from("Sunny"))
+    );
+    assert_eq!(transcript[3].text(), "It's sunny in Paris.");
+
+    let bodies: Vec<String> = bodies.try_iter().collect();
+    assert_eq!(bodies.len(), 2);
+    for body in &bodies {
+        assert_eq!(body.matches("Gets the weather of a city").count(), 1);
+        assert!(!body.contains("Outdated"));
+    }
+    assert!(bodies[1].contains(r#""tool_call_id":"call_1""#));
+}
+
+#[test]
+fn test_runner_gives_up_after_max_iterations() {
+    let (api_base, bodies) =
+        scripted_server(vec![tool_call_reply("call_1"), tool_call_reply("call_2")]);
+    let client = Client::new(String::from("keystring")).with_api_base(api_base);
+
+    let result = weather_runner()
+        .max_iterations(2)
+        .run(&client, chat_params());
+
+    let Err(OpenAIError::MaxIterationsReached {
+        iterations,
+        transcript,
+    }) = result
+    else {
+        panic!("expected MaxIterationsReached, got {:?}", result);
+    };
+    assert_eq!(iterations, 2);
+    assert_eq!(transcript.len(), 5);
+    assert_eq!(
+        transcript[4],
+        Message::tool(String::from("call_2"), String::from("Sunny"))
+    );
+    assert_eq!(bodies.try_iter().count(), 2);
+}
+
+#[test]
+fn test_runner_fails_on_cut_off_or_filtered_reply() {
+    let (api_base, bodies) = scripted_server(vec![
+        text_reply("It's sunny in", "length"),
+        text_reply("", "content_filter"),
+    ]);
+    let client = Client::new(String::from("keystring")).with_api_base(api_base);
+
+    let truncated = weather_runner().run(&client, chat_params());
+    assert!(matches!(truncated, Err(OpenAIError::Truncated)));
+
+    let filtered = weather_runner().run(&client, chat_params());
+    assert!(matches!(
+        filtered,
+        Err(OpenAIError::UnexpectedFinishReason(Some(
+            openai_rust::construct::FinishReason::ContentFilter
+        )))
+    ));
+    assert_eq!(bodies.try_iter().count(), 2);
+}